@@ -2,25 +2,141 @@
 // This file is part of the Azure Communication Services Email Client Library, an open-source project.
 // This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
 
-use crate::adapters::gateways::acs_shared_key::{get_request_header, parse_endpoint};
+use crate::adapters::gateways::acs_shared_key::{parse_endpoint, validate_host_suffix};
+use crate::adapters::gateways::config::{Config, ConfigCredential};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::adapters::gateways::email_sender::EmailSender;
+use crate::adapters::gateways::interceptor::Interceptor;
+use crate::adapters::gateways::metrics::MetricsSink;
+use crate::adapters::gateways::runtime::{delay, now};
+use crate::adapters::gateways::sent_log::{SentLog, SentRecord};
+use crate::adapters::gateways::signing::{get_host_authority, SharedKeySigner};
 use crate::domain::entities::models::{
-    EmailSendStatusType, ErrorDetail, ErrorResponse, SentEmail, SentEmailResponse,
+    AcsError, DomainResourceList, EmailSendStatusType, ErrorDetail, ErrorResponse, QueuedEmail,
+    RateLimitInfo, SentEmail, SentEmailResponse,
 };
-use azure_core::auth::TokenCredential;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::domain::entities::models::{EmailAddress, EmailContent, SentEmailBuilder};
+use azure_core::auth::{AccessToken, TokenCredential};
 use azure_core::HttpClient;
 use azure_identity::{create_credential, ClientSecretCredential};
-use log::{debug, error};
-use reqwest::header::RETRY_AFTER;
+use httpdate::fmt_http_date;
+use log::{debug, error, log_enabled, Level};
+use reqwest::header::{HeaderValue, RETRY_AFTER};
 use reqwest::{Client, StatusCode};
+use serde_json::value::RawValue;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+use std::collections::VecDeque;
+#[cfg(not(target_arch = "wasm32"))]
+use std::future::Future;
+use std::marker::PhantomData;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::oneshot;
-use tokio::time::sleep;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+use std::time::{Duration, SystemTime};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::{oneshot, Semaphore};
+use tokio::sync::{Mutex, OnceCell};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::task::JoinSet;
+#[cfg(feature = "cancellation")]
+use tokio_util::sync::CancellationToken;
 use url::Url;
 use uuid::Uuid;
 
-type EmailResult<T> = Result<T, ErrorResponse>;
+pub type EmailResult<T> = Result<T, AcsError>;
+/// The domain suffix connection string endpoints must end with in the public cloud, unless overridden.
+pub const DEFAULT_ACS_HOST_SUFFIX: &str = ".communication.azure.com";
 const API_VERSION: &str = "2023-01-15-preview";
+const DOMAINS_API_VERSION: &str = "2023-04-01";
+const MAX_BODY_SNIPPET_LEN: usize = 512;
+// How many Operation-Location URLs of unfinished sends a client keeps for status polling
+const MAX_OPERATION_LOCATIONS: usize = 1024;
+/// The number of times a throttled send is retried, unless changed with `ACSClientBuilder::max_retries`.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+/// The number of emails `ACSClient::send_personalized` sends at once.
+pub const DEFAULT_SEND_CONCURRENCY: usize = 8;
+/// How long acquiring a managed identity token may take, unless changed with
+/// `ACSClientBuilder::managed_identity_timeout`.
+pub const DEFAULT_MANAGED_IDENTITY_TIMEOUT: Duration = Duration::from_secs(10);
+// How long before it expires a cached access token is replaced
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+const MANAGED_IDENTITY_HINT: &str = "managed identity is only available on Azure hosts, use a connection string or service principal when running locally";
+#[cfg(not(target_arch = "wasm32"))]
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+#[cfg(not(target_arch = "wasm32"))]
+const STATUS_POLL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_TRANSIENT_POLL_ERRORS: u32 = 3;
+
+/// The Azure cloud hosting the ACS resource.
+///
+/// Selects the Azure AD authority host and token scopes for service principal and managed
+/// identity authentication, and the default endpoint suffix for connection strings. Both
+/// authentication methods read their scope and authority from here.
+///
+/// ```
+/// use azure_email_service::adapters::gateways::acs_email::Cloud;
+///
+/// assert_eq!(
+///     Cloud::Public.communication_scope(),
+///     "https://communication.azure.com/.default"
+/// );
+/// assert_eq!(Cloud::Public.authority_host(), "https://login.microsoftonline.com/");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cloud {
+    /// Azure public cloud.
+    #[default]
+    Public,
+
+    /// Azure US Government.
+    UsGov,
+
+    /// Azure China, operated by 21Vianet.
+    China,
+}
+
+impl Cloud {
+    /// The Azure AD authority host used to acquire tokens.
+    pub fn authority_host(&self) -> &'static str {
+        match self {
+            Cloud::Public => "https://login.microsoftonline.com/",
+            Cloud::UsGov => "https://login.microsoftonline.us/",
+            Cloud::China => "https://login.chinacloudapi.cn/",
+        }
+    }
+
+    /// The token scope of the ACS data plane.
+    pub fn communication_scope(&self) -> &'static str {
+        match self {
+            Cloud::Public => "https://communication.azure.com/.default",
+            Cloud::UsGov => "https://communication.azure.us/.default",
+            Cloud::China => "https://communication.azure.cn/.default",
+        }
+    }
+
+    /// The Azure Resource Manager endpoint, used to list email domains.
+    pub fn management_endpoint(&self) -> &'static str {
+        match self {
+            Cloud::Public => "https://management.azure.com/",
+            Cloud::UsGov => "https://management.usgovcloudapi.net/",
+            Cloud::China => "https://management.chinacloudapi.cn/",
+        }
+    }
+
+    /// The domain suffix of ACS endpoints.
+    pub fn host_suffix(&self) -> &'static str {
+        match self {
+            Cloud::Public => DEFAULT_ACS_HOST_SUFFIX,
+            Cloud::UsGov => ".communication.azure.us",
+            Cloud::China => ".communication.azure.cn",
+        }
+    }
+}
 
 // Azure Communication Services (ACS) authentication method
 #[derive(Clone)]
@@ -30,20 +146,185 @@ enum ACSAuthMethod {
         tenant_id: String,
         client_id: String,
         client_secret: String,
+        cloud: Cloud,
     },
-    ManagedIdentity,
+    ManagedIdentity {
+        cloud: Cloud,
+        token_timeout: Duration,
+    },
+    TokenCredential {
+        credential: Arc<dyn TokenCredential>,
+        cloud: Cloud,
+    },
+}
+
+impl ACSAuthMethod {
+    // The cloud tokens are acquired from, shared key authentication doesn't depend on it
+    fn cloud(&self) -> Cloud {
+        match self {
+            ACSAuthMethod::SharedKey(_) => Cloud::Public,
+            ACSAuthMethod::ServicePrincipal { cloud, .. }
+            | ACSAuthMethod::ManagedIdentity { cloud, .. }
+            | ACSAuthMethod::TokenCredential { cloud, .. } => *cloud,
+        }
+    }
+}
+
+// An access token for the ACS data plane and when it expires
+struct CachedToken {
+    token: String,
+    expires_on: SystemTime,
+}
+
+/// A fully signed request, as it would be sent to ACS.
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    /// The HTTP method of the request.
+    pub method: reqwest::Method,
+
+    /// The full URL of the request, including the query string.
+    pub url: String,
+
+    /// The request headers, including the `Authorization` header.
+    pub headers: reqwest::header::HeaderMap,
+
+    /// The serialized JSON body, empty for requests without a body.
+    pub body: String,
+}
+
+/// How `429` and `503` responses to a send are retried.
+#[derive(Clone, Copy, Debug)]
+struct RetryPolicy {
+    max_retries: u32,
+    // `None` waits as long as the retries take
+    max_total_retry_duration: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: DEFAULT_MAX_RETRIES,
+            max_total_retry_duration: None,
+        }
+    }
 }
 
+/// A client for the ACS email REST API.
+///
+/// Cloning is cheap: clones share the HTTP connection pool, credentials, cached access token and
+/// cached sender domains, so a clone can be moved into each spawned task.
+///
+/// ```no_run
+/// use azure_email_service::adapters::gateways::acs_email::ACSClient;
+/// use azure_email_service::domain::entities::models::SentEmail;
+///
+/// # async fn example(client: ACSClient, emails: Vec<SentEmail>) {
+/// for email in emails {
+///     let client = client.clone();
+///     tokio::spawn(async move { client.send_email(&email).await });
+/// }
+/// # }
+/// ```
 #[derive(Clone)]
 pub struct ACSClient {
+    inner: Arc<ACSClientInner>,
+}
+
+// The state shared by an `ACSClient` and its clones
+struct ACSClientInner {
     host: String,
+    // Pools connections across requests
+    http_client: Client,
     auth_method: ACSAuthMethod,
+    // Created on the first token request and shared by every request after it
+    credential: OnceCell<Arc<dyn TokenCredential>>,
+    access_token: Mutex<Option<CachedToken>>,
+    email_service_resource_id: Option<String>,
+    check_sender_domain: bool,
+    default_sender: Option<String>,
+    // The domain list is fetched at most once per client
+    sender_domains: OnceCell<Vec<String>>,
+    // The Operation-Location URL of each accepted send
+    operation_locations: OperationLocations,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    sent_log: Option<Arc<dyn SentLog>>,
+    interceptor: Option<Arc<dyn Interceptor>>,
+    retry_policy: RetryPolicy,
+    extra_query: Vec<(String, String)>,
+    #[cfg(feature = "cancellation")]
+    cancellation_token: Option<CancellationToken>,
 }
 
-pub struct ACSClientBuilder {
+// Status URLs of unfinished sends by operation ID, oldest first
+type OperationLocations = std::sync::Mutex<VecDeque<(String, String)>>;
+
+/// The builder state before an authentication method is chosen.
+pub struct Unauthenticated;
+
+/// The builder state after a `with_*` constructor chose the authentication method.
+pub struct Authenticated;
+
+/// Builds an `ACSClient`.
+///
+/// Start with one of the `with_*` constructors, which fix the host and authentication method
+/// so they can't be combined by mistake, then set any options and call `build`.
+///
+/// ```
+/// use azure_email_service::adapters::gateways::acs_email::ACSClientBuilder;
+///
+/// let client = ACSClientBuilder::with_connection_string(
+///     "endpoint=https://xxx.communication.azure.com/;accesskey=c2VjcmV0",
+/// )
+/// .max_retries(5)
+/// .build();
+/// assert!(client.is_ok());
+///
+/// let client = ACSClientBuilder::with_shared_key("xxx.communication.azure.com", "c2VjcmV0").build();
+/// assert!(client.is_ok());
+///
+/// let client = ACSClientBuilder::with_managed_identity("xxx.communication.azure.com").build();
+/// assert!(client.is_ok());
+///
+/// // Behind a proxy that breaks HTTP/2
+/// let client = ACSClientBuilder::with_managed_identity("xxx.communication.azure.com")
+///     .http1_only()
+///     .build();
+/// assert!(client.is_ok());
+/// ```
+pub struct ACSClientBuilder<Auth = Unauthenticated> {
     host: Option<String>,
     connection_string: Option<String>,
+    host_suffix: Option<String>,
+    cloud: Cloud,
     auth_method: Option<ACSAuthMethod>,
+    email_service_resource_id: Option<String>,
+    check_sender_domain: bool,
+    default_sender: Option<String>,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    sent_log: Option<Arc<dyn SentLog>>,
+    interceptor: Option<Arc<dyn Interceptor>>,
+    retry_policy: RetryPolicy,
+    extra_query: Vec<(String, String)>,
+    managed_identity_timeout: Duration,
+    #[cfg(not(target_arch = "wasm32"))]
+    max_idle_connections: Option<usize>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_idle_timeout: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    root_certificates: Vec<Vec<u8>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    accept_invalid_certs: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    http1_only: bool,
+    #[cfg(feature = "cancellation")]
+    cancellation_token: Option<CancellationToken>,
+    auth: PhantomData<Auth>,
+}
+
+impl Default for ACSClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ACSClientBuilder {
@@ -52,23 +333,235 @@ impl ACSClientBuilder {
         ACSClientBuilder {
             host: None,
             connection_string: None,
+            host_suffix: None,
+            cloud: Cloud::Public,
             auth_method: None,
+            email_service_resource_id: None,
+            check_sender_domain: false,
+            default_sender: None,
+            metrics: None,
+            sent_log: None,
+            interceptor: None,
+            retry_policy: RetryPolicy::default(),
+            extra_query: Vec::new(),
+            managed_identity_timeout: DEFAULT_MANAGED_IDENTITY_TIMEOUT,
+            #[cfg(not(target_arch = "wasm32"))]
+            max_idle_connections: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_idle_timeout: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            root_certificates: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            accept_invalid_certs: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            http1_only: false,
+            #[cfg(feature = "cancellation")]
+            cancellation_token: None,
+            auth: PhantomData,
+        }
+    }
+
+    /// Create a builder from environment variables, picking the authentication method.
+    ///
+    /// See `Config::from_env` for the variables that are read.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ACSClientBuilder<Authenticated>, String>` - The configured builder, or an error if no configuration is found.
+    pub fn from_env() -> Result<ACSClientBuilder<Authenticated>, String> {
+        Config::from_env().map(Self::from_config)
+    }
+
+    /// Create a builder from a validated configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration, e.g. from `Config::from_env` or `Config::from_connection_string`.
+    ///
+    /// # Returns
+    ///
+    /// * `ACSClientBuilder<Authenticated>` - The builder with the configured host and authentication method.
+    pub fn from_config(config: Config) -> ACSClientBuilder<Authenticated> {
+        match config.credential {
+            ConfigCredential::ConnectionString(connection_string) => {
+                Self::with_connection_string(&connection_string)
+            }
+            ConfigCredential::ServicePrincipal {
+                endpoint,
+                tenant_id,
+                client_id,
+                client_secret,
+            } => Self::with_service_principal(&endpoint, &tenant_id, &client_id, &client_secret),
+            ConfigCredential::ManagedIdentity { endpoint } => {
+                Self::with_managed_identity(&endpoint)
+            }
         }
     }
 
+    /// Create a builder authenticating with a shared key from a connection string.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection_string` - A string slice that holds the `endpoint=...;accesskey=...` connection string.
+    ///
+    /// # Returns
+    ///
+    /// * `ACSClientBuilder<Authenticated>` - The builder, validated when it is built.
+    pub fn with_connection_string(connection_string: &str) -> ACSClientBuilder<Authenticated> {
+        let mut builder = Self::new();
+        builder.connection_string = Some(connection_string.to_string());
+        builder.authenticated()
+    }
+
+    /// Create a builder authenticating with a shared key stored separately from the endpoint,
+    /// e.g. in two Key Vault secrets.
+    ///
+    /// The host is normalized like a connection string endpoint, so both sign the same requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The ACS endpoint, e.g. `contoso.communication.azure.com` or `https://contoso.communication.azure.com/`.
+    ///   A loopback `http://` endpoint, e.g. a local mock server, is accepted too.
+    /// * `access_key` - The base64 encoded access key of the resource.
+    ///
+    /// # Returns
+    ///
+    /// * `ACSClientBuilder<Authenticated>` - The builder, validated when it is built.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::adapters::gateways::acs_email::ACSClientBuilder;
+    /// use azure_email_service::domain::entities::models::SentEmailBuilder;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let email = SentEmailBuilder::new()
+    ///     .sender("donotreply@example.com".to_string())
+    ///     .subject("Subject")
+    ///     .plain_text("Body")
+    ///     .to("jane@example.com")
+    ///     .build()
+    ///     .unwrap();
+    /// let from_key = ACSClientBuilder::with_shared_key("https://XXX.communication.azure.com/", "c2VjcmV0")
+    ///     .build()
+    ///     .unwrap()
+    ///     .build_send_request(&email)
+    ///     .await
+    ///     .unwrap();
+    /// let from_connection_string = ACSClientBuilder::with_connection_string(
+    ///     "endpoint=https://xxx.communication.azure.com/;accesskey=c2VjcmV0",
+    /// )
+    /// .build()
+    /// .unwrap()
+    /// .build_send_request(&email)
+    /// .await
+    /// .unwrap();
+    /// assert_eq!(from_key.url, from_connection_string.url);
+    /// assert_eq!(
+    ///     from_key.headers["x-ms-content-sha256"],
+    ///     from_connection_string.headers["x-ms-content-sha256"]
+    /// );
+    /// assert_eq!(from_key.body, from_connection_string.body);
+    /// # }
+    /// ```
+    pub fn with_shared_key(host: &str, access_key: &str) -> ACSClientBuilder<Authenticated> {
+        let mut builder = Self::new();
+        builder.host = Some(host.to_string());
+        builder.auth_method = Some(ACSAuthMethod::SharedKey(access_key.to_string()));
+        builder.authenticated()
+    }
+
+    /// Create a builder authenticating with a service principal.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The ACS endpoint, e.g. `contoso.communication.azure.com`.
+    /// * `tenant_id` - The Azure AD tenant of the service principal.
+    /// * `client_id` - The client ID of the service principal.
+    /// * `client_secret` - The client secret of the service principal.
+    ///
+    /// # Returns
+    ///
+    /// * `ACSClientBuilder<Authenticated>` - The builder, validated when it is built.
+    pub fn with_service_principal(
+        host: &str,
+        tenant_id: &str,
+        client_id: &str,
+        client_secret: &str,
+    ) -> ACSClientBuilder<Authenticated> {
+        let mut builder = Self::new();
+        builder.host = Some(host.to_string());
+        builder.auth_method = Some(ACSAuthMethod::ServicePrincipal {
+            tenant_id: tenant_id.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            cloud: Cloud::Public,
+        });
+        builder.authenticated()
+    }
+
+    /// Create a builder authenticating with the managed identity of the Azure host.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The ACS endpoint, e.g. `contoso.communication.azure.com`.
+    ///
+    /// # Returns
+    ///
+    /// * `ACSClientBuilder<Authenticated>` - The builder, validated when it is built.
+    pub fn with_managed_identity(host: &str) -> ACSClientBuilder<Authenticated> {
+        let mut builder = Self::new();
+        builder.host = Some(host.to_string());
+        builder.auth_method = Some(ACSAuthMethod::ManagedIdentity {
+            cloud: Cloud::Public,
+            token_timeout: DEFAULT_MANAGED_IDENTITY_TIMEOUT,
+        });
+        builder.authenticated()
+    }
+
+    /// Create a builder authenticating with any Azure token credential, e.g. a workload identity
+    /// or Azure CLI credential from `azure_identity`.
+    ///
+    /// Tokens are requested for the ACS scope of the selected cloud and cached by the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The ACS endpoint, e.g. `contoso.communication.azure.com`.
+    /// * `credential` - The credential to acquire access tokens with.
+    ///
+    /// # Returns
+    ///
+    /// * `ACSClientBuilder<Authenticated>` - The builder, validated when it is built.
+    pub fn with_token_credential(
+        host: &str,
+        credential: Arc<dyn TokenCredential>,
+    ) -> ACSClientBuilder<Authenticated> {
+        let mut builder = Self::new();
+        builder.host = Some(host.to_string());
+        builder.auth_method = Some(ACSAuthMethod::TokenCredential {
+            credential,
+            cloud: Cloud::Public,
+        });
+        builder.authenticated()
+    }
+
     // Set the host for the client
+    #[deprecated(note = "use the `ACSClientBuilder::with_*` constructors")]
     pub fn host(mut self, host: &str) -> Self {
         self.host = Some(host.to_string());
         self
     }
 
     // Set the authentication method for the client using a shared key
+    #[deprecated(note = "use `ACSClientBuilder::with_connection_string`")]
     pub fn connection_string(mut self, connection_string: &str) -> Self {
         self.connection_string = Some(connection_string.to_string());
         self
     }
 
     // Set the authentication method for the client using a service principal
+    #[deprecated(note = "use `ACSClientBuilder::with_service_principal`")]
     pub fn service_principal(
         mut self,
         tenant_id: &str,
@@ -79,31 +572,390 @@ impl ACSClientBuilder {
             tenant_id: tenant_id.to_string(),
             client_id: client_id.to_string(),
             client_secret: client_secret.to_string(),
+            cloud: Cloud::Public,
         });
         self
     }
 
     // Set the authentication method for the client using managed identity
+    #[deprecated(note = "use `ACSClientBuilder::with_managed_identity`")]
     pub fn managed_identity(mut self) -> Self {
-        self.auth_method = Some(ACSAuthMethod::ManagedIdentity);
+        self.auth_method = Some(ACSAuthMethod::ManagedIdentity {
+            cloud: Cloud::Public,
+            token_timeout: DEFAULT_MANAGED_IDENTITY_TIMEOUT,
+        });
+        self
+    }
+
+    // Set the authentication method for the client using any Azure token credential
+    #[deprecated(note = "use `ACSClientBuilder::with_token_credential`")]
+    pub fn token_credential(mut self, credential: Arc<dyn TokenCredential>) -> Self {
+        self.auth_method = Some(ACSAuthMethod::TokenCredential {
+            credential,
+            cloud: Cloud::Public,
+        });
+        self
+    }
+
+    // Move the configuration into a builder whose authentication can no longer change
+    fn authenticated(self) -> ACSClientBuilder<Authenticated> {
+        ACSClientBuilder {
+            host: self.host,
+            connection_string: self.connection_string,
+            host_suffix: self.host_suffix,
+            cloud: self.cloud,
+            auth_method: self.auth_method,
+            email_service_resource_id: self.email_service_resource_id,
+            check_sender_domain: self.check_sender_domain,
+            default_sender: self.default_sender,
+            metrics: self.metrics,
+            sent_log: self.sent_log,
+            interceptor: self.interceptor,
+            retry_policy: self.retry_policy,
+            extra_query: self.extra_query,
+            managed_identity_timeout: self.managed_identity_timeout,
+            #[cfg(not(target_arch = "wasm32"))]
+            max_idle_connections: self.max_idle_connections,
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_idle_timeout: self.pool_idle_timeout,
+            #[cfg(not(target_arch = "wasm32"))]
+            root_certificates: self.root_certificates,
+            #[cfg(not(target_arch = "wasm32"))]
+            accept_invalid_certs: self.accept_invalid_certs,
+            #[cfg(not(target_arch = "wasm32"))]
+            http1_only: self.http1_only,
+            #[cfg(feature = "cancellation")]
+            cancellation_token: self.cancellation_token,
+            auth: PhantomData,
+        }
+    }
+}
+
+impl<Auth> ACSClientBuilder<Auth> {
+    // Set the Azure cloud hosting the resource, `Cloud::Public` by default
+    pub fn cloud(mut self, cloud: Cloud) -> Self {
+        self.cloud = cloud;
+        self
+    }
+
+    // Override the domain suffix that shared key endpoints must end with.
+    // Defaults to the suffix of the selected cloud.
+    pub fn host_suffix(mut self, host_suffix: &str) -> Self {
+        self.host_suffix = Some(host_suffix.to_string());
+        self
+    }
+
+    // Set how long acquiring a managed identity token may take, `DEFAULT_MANAGED_IDENTITY_TIMEOUT`
+    // by default. Off Azure there is no identity endpoint, so this bounds how long a send waits
+    // before failing.
+    pub fn managed_identity_timeout(mut self, managed_identity_timeout: Duration) -> Self {
+        self.managed_identity_timeout = managed_identity_timeout;
+        self
+    }
+
+    // Set the ARM resource ID of the Email Communication Service used by `verify_sender_domain`,
+    // e.g. `/subscriptions/{id}/resourceGroups/{group}/providers/Microsoft.Communication/emailServices/{name}`
+    pub fn email_service_resource_id(mut self, resource_id: &str) -> Self {
+        self.email_service_resource_id = Some(resource_id.to_string());
+        self
+    }
+
+    // Reject sends whose sender address has an obviously malformed domain before calling ACS
+    pub fn check_sender_domain(mut self, check_sender_domain: bool) -> Self {
+        self.check_sender_domain = check_sender_domain;
+        self
+    }
+
+    // Send emails with a blank sender from this address. A sender set on the email always wins.
+    pub fn default_sender(mut self, sender: &str) -> Self {
+        self.default_sender = Some(sender.to_string());
+        self
+    }
+
+    // Record send counts, retries and latency in the given sink
+    pub fn metrics(mut self, metrics: Arc<dyn MetricsSink>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    // Record the operation ID of every accepted email in the given log, for later reconciliation
+    pub fn sent_log(mut self, sent_log: Arc<dyn SentLog>) -> Self {
+        self.sent_log = Some(sent_log);
+        self
+    }
+
+    // Observe every send and status request and response, e.g. for audit logs
+    pub fn interceptor(mut self, interceptor: Arc<dyn Interceptor>) -> Self {
+        self.interceptor = Some(interceptor);
+        self
+    }
+
+    // Set how often `429` and `503` responses to a send are retried, `DEFAULT_MAX_RETRIES` by default
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.retry_policy.max_retries = max_retries;
+        self
+    }
+
+    // Bound the total time a send spends retrying. A retry whose wait would exceed the budget
+    // fails with `AcsError::Timeout` instead of sleeping, e.g. on repeated long `Retry-After`s.
+    pub fn max_total_retry_duration(mut self, max_total_retry_duration: Duration) -> Self {
+        self.retry_policy.max_total_retry_duration = Some(max_total_retry_duration);
+        self
+    }
+
+    // Return throttling errors on the first `429` or `503` without sleeping, for callers with
+    // their own retry layer. A `401` with token authentication is still retried once.
+    pub fn disable_retries(self) -> Self {
+        self.max_retries(0)
+    }
+
+    // Append a query parameter to the send and status URLs after `api-version`, e.g. for
+    // debugging or preview features. Parameters are signed in the order they are added.
+    pub fn extra_query(mut self, key: &str, value: &str) -> Self {
+        self.extra_query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    // Set the maximum number of idle connections kept open to the ACS host, unlimited by default.
+    // A service sending thousands of emails per minute is well served by 32 to 64.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn max_idle_connections(mut self, max_idle_connections: usize) -> Self {
+        self.max_idle_connections = Some(max_idle_connections);
+        self
+    }
+
+    // Set how long an idle connection is kept open, 90 seconds by default. Keep it below the
+    // idle timeout of any proxy or load balancer in between, e.g. 60 seconds.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+
+    // Trust an additional PEM encoded root certificate, e.g. the CA of a TLS inspecting proxy.
+    // Applies to ACS and service principal token requests. Every host whose certificate chains
+    // to this root is trusted, so only add CAs whose private keys are under your control.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Self {
+        self.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    // Accept any server certificate, including expired, self-signed and wrong-host ones.
+    // Only for test environments: anyone on the network path can then read the access key's
+    // signed requests or the bearer token, and impersonate ACS.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn danger_accept_invalid_certs(mut self) -> Self {
+        self.accept_invalid_certs = true;
+        self
+    }
+
+    // Only use HTTP/1.1, e.g. behind a proxy that resets HTTP/2 streams to ACS intermittently.
+    // Applies to ACS and service principal token requests. Without it, HTTP/2 is used when the
+    // server offers it, which multiplexes concurrent sends over fewer connections.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http1_only(mut self) -> Self {
+        self.http1_only = true;
+        self
+    }
+
+    // Set a token that interrupts status polling when cancelled, e.g. on service shutdown
+    #[cfg(feature = "cancellation")]
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = Some(cancellation_token);
         self
     }
 
-    // Build and return the ACSClient
+    /// Build the client as a shared `EmailSender` trait object, e.g. for web framework state.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use axum::{extract::State, routing::post, Json, Router};
+    ///
+    /// async fn send(
+    ///     State(sender): State<Arc<dyn EmailSender>>,
+    ///     Json(email): Json<SentEmail>,
+    /// ) -> Result<String, String> {
+    ///     sender.send_email(&email).await.map_err(|e| e.to_string())
+    /// }
+    ///
+    /// let sender = ACSClientBuilder::from_env()?.build_arc()?;
+    /// let app = Router::new().route("/send", post(send)).with_state(sender);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Arc<dyn EmailSender>, String>` - The client, or an error if the configuration is invalid.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn build_arc(self) -> Result<Arc<dyn EmailSender>, String> {
+        Ok(Arc::new(self.build()?))
+    }
+
+    // Build and return the ACSClient. A connection string can't be combined with an
+    // authentication method, or with a host other than the connection string's endpoint.
     pub fn build(self) -> Result<ACSClient, String> {
-        if let Some(connection_string) = self.connection_string {
+        let (host, auth_method) = if let Some(connection_string) = self.connection_string {
             let parsed_res = parse_endpoint(&connection_string)
                 .map_err(|e| format!("Failed to parse connection string: {}", e))?;
-            let host = parsed_res.host_name;
-            let auth_method = ACSAuthMethod::SharedKey(parsed_res.access_key);
-            return Ok(ACSClient { host, auth_method });
+            // The access key signs every request, so never send it to an unexpected host
+            validate_host_suffix(
+                parsed_res.host_name.trim_start_matches("http://"),
+                self.host_suffix
+                    .as_deref()
+                    .unwrap_or(self.cloud.host_suffix()),
+            )?;
+            // A host matching the connection string's endpoint is redundant, anything else is ambiguous
+            let host_conflicts = self.host.as_deref().is_some_and(|host| {
+                !host
+                    .trim_start_matches("https://")
+                    .trim_end_matches('/')
+                    .eq_ignore_ascii_case(&parsed_res.host_name)
+            });
+            if host_conflicts || self.auth_method.is_some() {
+                return Err("Specify either a connection string or host+auth, not both".to_string());
+            }
+            (
+                parsed_res.host_name,
+                ACSAuthMethod::SharedKey(parsed_res.access_key),
+            )
+        } else {
+            let mut host = self.host.ok_or_else(|| "Host is required".to_string())?;
+            let mut auth_method = self
+                .auth_method
+                .ok_or_else(|| "Authentication method is required".to_string())?;
+            match &mut auth_method {
+                ACSAuthMethod::ServicePrincipal { cloud, .. }
+                | ACSAuthMethod::TokenCredential { cloud, .. } => *cloud = self.cloud,
+                ACSAuthMethod::ManagedIdentity {
+                    cloud,
+                    token_timeout,
+                } => {
+                    *cloud = self.cloud;
+                    *token_timeout = self.managed_identity_timeout;
+                }
+                ACSAuthMethod::SharedKey(_) => {
+                    // Normalize like a connection string endpoint, e.g. dropping a trailing `/`
+                    let (scheme, authority) = match host.strip_prefix("http://") {
+                        Some(authority) => ("http", authority),
+                        None => ("https", host.trim_start_matches("https://")),
+                    };
+                    let url = Url::parse(&format!("{}://{}", scheme, authority))
+                        .map_err(|e| format!("Invalid endpoint URL: {}", e))?;
+                    let authority = get_host_authority(&url)?;
+                    // The access key signs every request, so never send it to an unexpected host
+                    validate_host_suffix(
+                        &authority,
+                        self.host_suffix
+                            .as_deref()
+                            .unwrap_or(self.cloud.host_suffix()),
+                    )?;
+                    host = match scheme {
+                        "http" => format!("http://{}", authority),
+                        _ => authority,
+                    };
+                }
+            }
+            (host, auth_method)
+        };
+        // Plain HTTP exposes the signature or bearer token, so only a local server may use it
+        if host.starts_with("http://") {
+            let url = Url::parse(&host).map_err(|e| format!("Invalid endpoint URL: {}", e))?;
+            let is_loopback = match url.host() {
+                Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+                Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+                Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+                None => false,
+            };
+            if !is_loopback {
+                return Err(
+                    "Plain HTTP endpoints are only allowed for loopback hosts, e.g. a local mock server"
+                        .to_string(),
+                );
+            }
         }
 
-        let host = self.host.ok_or_else(|| "Host is required".to_string())?;
-        let auth_method = self
-            .auth_method
-            .ok_or_else(|| "Authentication method is required".to_string())?;
-        Ok(ACSClient { host, auth_method })
+        #[allow(unused_mut)]
+        let mut http_client = Client::builder();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(max_idle_connections) = self.max_idle_connections {
+                http_client = http_client.pool_max_idle_per_host(max_idle_connections);
+            }
+            if let Some(pool_idle_timeout) = self.pool_idle_timeout {
+                http_client = http_client.pool_idle_timeout(pool_idle_timeout);
+            }
+            for pem in &self.root_certificates {
+                let certificate = reqwest::Certificate::from_pem(pem)
+                    .map_err(|e| format!("Invalid root certificate: {}", e))?;
+                http_client = http_client.add_root_certificate(certificate);
+            }
+            if self.accept_invalid_certs {
+                log::warn!("TLS certificate validation is disabled");
+                http_client = http_client.danger_accept_invalid_certs(true);
+            }
+            if self.http1_only {
+                http_client = http_client.http1_only();
+            }
+        }
+        let http_client = http_client
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        Ok(ACSClient {
+            inner: Arc::new(ACSClientInner {
+                host,
+                http_client,
+                auth_method,
+                credential: OnceCell::new(),
+                access_token: Mutex::new(None),
+                email_service_resource_id: self.email_service_resource_id,
+                check_sender_domain: self.check_sender_domain,
+                default_sender: self.default_sender,
+                sender_domains: OnceCell::new(),
+                operation_locations: std::sync::Mutex::new(VecDeque::new()),
+                metrics: self.metrics,
+                sent_log: self.sent_log,
+                interceptor: self.interceptor,
+                retry_policy: self.retry_policy,
+                extra_query: self.extra_query,
+                #[cfg(feature = "cancellation")]
+                cancellation_token: self.cancellation_token,
+            }),
+        })
+    }
+}
+
+impl TryFrom<&str> for ACSClient {
+    type Error = String;
+
+    /// Builds a shared key client from a connection string, like
+    /// `ACSClientBuilder::with_connection_string(connection_string).build()`.
+    ///
+    /// ```
+    /// use azure_email_service::adapters::gateways::acs_email::ACSClient;
+    ///
+    /// let client = ACSClient::try_from(
+    ///     "endpoint=https://xxx.communication.azure.com/;accesskey=c2VjcmV0",
+    /// );
+    /// assert!(client.is_ok());
+    /// assert!(ACSClient::try_from("not a connection string").is_err());
+    /// ```
+    fn try_from(connection_string: &str) -> Result<Self, Self::Error> {
+        ACSClientBuilder::with_connection_string(connection_string).build()
+    }
+}
+
+// Sends on the wrapped channel when dropped, so the receiver is always notified exactly once
+#[cfg(not(target_arch = "wasm32"))]
+struct CompletionSignal(Option<oneshot::Sender<()>>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for CompletionSignal {
+    fn drop(&mut self) {
+        if let Some(tx) = self.0.take() {
+            let _ = tx.send(());
+        }
     }
 }
 
@@ -117,312 +969,1547 @@ impl ACSClient {
     /// # Returns
     ///
     /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if the email has no subject, body or recipients, or a malformed sender,
+    /// recipient or reply-to address, e.g. because it was built without `SentEmailBuilder`.
+    /// Release builds send the email as is, and the server rejects invalid values.
     pub async fn send_email(&self, email: &SentEmail) -> EmailResult<String> {
         let request_id = format!("{}", Uuid::new_v4());
-        acs_send_email(&self.host, &self.auth_method, request_id.as_str(), email).await
+        self.send_email_with_id(email, request_id.as_str()).await
     }
-    /// Sends an email using the ACS client and periodically checks the status, invoking a callback function with the status.
+
+    /// Send an email using a caller-supplied repeatability request ID.
+    ///
+    /// ACS deduplicates sends carrying the same `repeatability-request-id`, so retrying the same
+    /// logical send with the same ID will not deliver the email twice. The internal retry loop
+    /// (on `429`/`503`) always reuses this ID for each of its own attempts.
     ///
     /// # Arguments
     ///
     /// * `email` - A reference to the `SentEmail` struct containing the email details.
-    /// * `call_back` - A callback function that takes the message ID, email send status, and optional error details.
+    /// * `request_id` - The repeatability request ID, typically a UUID. An ID that isn't a valid
+    ///   header value, e.g. one containing a newline, fails before anything is sent.
     ///
     /// # Returns
     ///
     /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
-    #[allow(dead_code)]
-    pub async fn send_email_with_callback<F>(
-        self,
+    pub async fn send_email_with_id(
+        &self,
         email: &SentEmail,
-        call_back: F,
-    ) -> EmailResult<(String, oneshot::Receiver<()>)>
-    where
-        F: Fn(String, &EmailSendStatusType, Option<ErrorDetail>) + Send + Sync + 'static,
-    {
-        let request_id = format!("{}", Uuid::new_v4());
-        let result =
-            acs_send_email(&self.host, &self.auth_method, request_id.as_str(), email).await?;
-
-        let message_id = result.clone();
-        let (tx, rx) = oneshot::channel();
-        tokio::spawn(async move {
-            loop {
-                sleep(Duration::from_secs(5)).await;
-                let resp_status = self.get_email_status(&message_id).await;
-                if let Ok(status) = resp_status {
-                    call_back(message_id.clone(), &status, None);
-                    if matches!(
-                        status,
-                        EmailSendStatusType::Unknown
-                            | EmailSendStatusType::Canceled
-                            | EmailSendStatusType::Failed
-                            | EmailSendStatusType::Succeeded
-                    ) {
-                        let _ = tx.send(());
-                        break;
-                    }
-                } else {
-                    call_back(
-                        message_id.clone(),
-                        &EmailSendStatusType::Failed,
-                        Some(ErrorDetail {
-                            message: Some(format!("Error getting email status: {:?}", resp_status)),
-                            ..Default::default()
-                        }),
-                    );
-                    let _ = tx.send(());
-                    break;
-                }
-            }
-        });
-
-        Ok((result, rx))
+        request_id: &str,
+    ) -> EmailResult<String> {
+        self.send_email_with_id_and_version(email, request_id, None)
+            .await
     }
 
-    /// Get the status of a sent email using the ACS client.
+    /// Send an email with a specific API version instead of the client's default.
+    ///
+    /// Useful during a migration, when the send endpoint needs a newer API version than status
+    /// queries, or the other way around. The version is part of the signed URL. Pair it with
+    /// `get_email_status_with_version` to poll with a different version.
     ///
     /// # Arguments
     ///
-    /// * `message_id` - A reference to the message ID string.
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    /// * `api_version` - The `api-version` to send with, e.g. `2023-03-31`.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use azure_email_service::adapters::gateways::acs_email::ACSClientBuilder;
+    /// use azure_email_service::domain::entities::models::SentEmailBuilder;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ACSClientBuilder::with_connection_string(
+    ///     "endpoint=https://xxx.communication.azure.com/;accesskey=c2VjcmV0",
+    /// )
+    /// .build()?;
+    /// let email = SentEmailBuilder::new()
+    ///     .sender("donotreply@example.com".to_string())
+    ///     .subject("Subject")
+    ///     .plain_text("Body")
+    ///     .to("jane@example.com")
+    ///     .build()?;
+    ///
+    /// let message_id = client.send_email_with_version(&email, "2023-03-31").await?;
+    /// let status = client
+    ///     .get_email_status_with_version(&message_id, "2023-01-15-preview")
+    ///     .await?;
+    /// # let _ = status;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_email_with_version(
+        &self,
+        email: &SentEmail,
+        api_version: &str,
+    ) -> EmailResult<String> {
+        let request_id = format!("{}", Uuid::new_v4());
+        self.send_email_with_id_and_version(email, &request_id, Some(api_version))
+            .await
+    }
+
+    // Send with the given request ID, and the given or the default API version
+    async fn send_email_with_id_and_version(
+        &self,
+        email: &SentEmail,
+        request_id: &str,
+        api_version: Option<&str>,
+    ) -> EmailResult<String> {
+        let email = self.prepare_sender(email)?;
+        acs_send_email(
+            &self.inner,
+            &self.send_email_url(api_version),
+            request_id,
+            &email,
+        )
+        .await
+    }
+
+    /// Send a queued email, reusing its request ID so replays are deduplicated by ACS.
+    ///
+    /// The email is sent immediately; honoring `scheduled_at` is up to the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `queued` - A reference to the `QueuedEmail` to send.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+    pub async fn send_queued(&self, queued: &QueuedEmail) -> EmailResult<String> {
+        self.send_email_with_id(&queued.email, &queued.request_id)
+            .await
+    }
+
+    /// Send an email with a repeatability request ID derived from an idempotency key.
+    ///
+    /// The same key always maps to the same request ID, so an application-level retry after a
+    /// timeout, where ACS may already have received the first request, doesn't deliver the email
+    /// twice. Use a key that identifies the logical email, e.g. an order or notification ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    /// * `key` - The idempotency key.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+    pub async fn send_email_idempotent(&self, email: &SentEmail, key: &str) -> EmailResult<String> {
+        self.send_email_with_id(email, &idempotency_request_id(key))
+            .await
+    }
+
+    /// Send an already serialized JSON body to the `emails:send` endpoint as is.
+    ///
+    /// The body is only checked to be syntactically valid JSON; the caller is responsible for it
+    /// matching the ACS send email schema. The exact bytes are signed and sent, without
+    /// surrounding whitespace. Sender checks and the default sender don't apply.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The JSON request body.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+    pub async fn send_raw_json(&self, body: &str) -> EmailResult<String> {
+        let body = RawValue::from_string(body.to_string())
+            .map_err(|e| to_error_response("Invalid JSON body", e))?;
+        let request_id = format!("{}", Uuid::new_v4());
+        acs_send_body(
+            &self.inner,
+            &self.send_email_url(None),
+            request_id.as_str(),
+            &body,
+        )
+        .await
+    }
+
+    /// Build the signed send request for an email without performing any network I/O.
+    ///
+    /// Useful for debugging signature mismatches, since the returned headers contain the exact
+    /// `Authorization`, `x-ms-date` and `x-ms-content-sha256` values that would be sent.
+    /// Service principal and managed identity clients still acquire an access token.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<PreparedRequest>` - The prepared request if successful.
+    pub async fn build_send_request(&self, email: &SentEmail) -> EmailResult<PreparedRequest> {
+        let email = self.prepare_sender(email)?;
+        let request_id = format!("{}", Uuid::new_v4());
+        prepare_request(
+            &self.inner,
+            reqwest::Method::POST,
+            &self.send_email_url(None),
+            request_id.as_str(),
+            Some(email.as_ref()),
+        )
+        .await
+    }
+
+    /// Get the status of a sent email using the ACS client.
+    ///
+    /// An ID returned by this client's sends is polled at the `Operation-Location` URL of its
+    /// response, if it had one, so any API version or query the service chose is kept. An error
+    /// embedded in the operation, e.g. on a `Failed` status, is returned as `AcsError::Api`. Use
+    /// `get_email_operation` to read it alongside the status instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_id` - The message ID, or the full operation URL from an `Operation-Location`
+    ///   header, which must point at the client's host.
     ///
     /// # Returns
     ///
     /// * `EmailResult<EmailSendStatusType>` - The result of the email status query, containing the status if successful.
     pub async fn get_email_status(&self, message_id: &str) -> EmailResult<EmailSendStatusType> {
-        acs_get_email_status(&self.host, &self.auth_method, message_id).await
+        self.get_email_status_with(message_id, None).await
+    }
+
+    /// Get the status of a sent email with a specific API version instead of the client's default.
+    ///
+    /// The version replaces any `api-version` of a full operation URL, and is part of the
+    /// signed URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_id` - The message ID, or the full operation URL from an `Operation-Location`
+    ///   header, which must point at the client's host.
+    /// * `api_version` - The `api-version` to query with, e.g. `2023-03-31`.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<EmailSendStatusType>` - The result of the email status query, containing the status if successful.
+    pub async fn get_email_status_with_version(
+        &self,
+        message_id: &str,
+        api_version: &str,
+    ) -> EmailResult<EmailSendStatusType> {
+        self.get_email_status_with(message_id, Some(api_version))
+            .await
+    }
+
+    // Get the status with the given or the default API version
+    async fn get_email_status_with(
+        &self,
+        message_id: &str,
+        api_version: Option<&str>,
+    ) -> EmailResult<EmailSendStatusType> {
+        let operation = self
+            .get_email_operation_with(message_id, api_version)
+            .await?;
+        if let Some(error) = operation.error {
+            return Err(AcsError::api(
+                Some(StatusCode::OK.as_u16()),
+                ErrorResponse { error: Some(error) },
+            ));
+        }
+        operation
+            .status
+            .map(|status| status.to_type())
+            .ok_or_else(create_missing_status_error)
+    }
+
+    /// Get the full operation of a sent email, including its ID and any error details.
+    ///
+    /// Like `get_email_status`, an ID returned by this client's sends is polled at the
+    /// `Operation-Location` URL of its response, if it had one.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_id` - The message ID, or the full operation URL from an `Operation-Location`
+    ///   header, which must point at the client's host.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<SentEmailResponse>` - The parsed operation if successful.
+    pub async fn get_email_operation(&self, message_id: &str) -> EmailResult<SentEmailResponse> {
+        self.get_email_operation_with(message_id, None).await
+    }
+
+    // Get the operation with the given or the default API version
+    async fn get_email_operation_with(
+        &self,
+        message_id: &str,
+        api_version: Option<&str>,
+    ) -> EmailResult<SentEmailResponse> {
+        let location = operation_location_for(&self.inner.operation_locations, message_id);
+        let operation =
+            acs_get_email_operation(&self.inner, message_id, location.as_deref(), api_version)
+                .await?;
+        if operation
+            .status
+            .is_some_and(|status| status.to_type().is_terminal())
+        {
+            forget_operation_location(&self.inner.operation_locations, message_id);
+        }
+        Ok(operation)
+    }
+
+    /// Get an access token for the client's service principal, managed identity or token credential.
+    ///
+    /// The token is cached and shared with the client's requests until shortly before it expires.
+    /// It is scoped to the ACS data plane of the client's cloud, e.g.
+    /// `https://communication.azure.com/.default`, so it can also be used for XOAUTH2
+    /// authentication with the ACS SMTP endpoint.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The access token, or an error when the client uses a shared key.
+    pub async fn access_token(&self) -> EmailResult<String> {
+        if let ACSAuthMethod::SharedKey(_) = self.inner.auth_method {
+            return Err(AcsError::Client(
+                "Access tokens require service principal, managed identity or token credential authentication"
+                    .to_string(),
+            ));
+        }
+        get_access_token(&self.inner)
+            .await
+            .map_err(|e| to_error_response("Failed to acquire access token", e))
+    }
+
+    /// Check whether the domain of a sender address is provisioned in the Email Communication Service.
+    ///
+    /// Requires `email_service_resource_id` and a service principal or managed identity with read
+    /// access to the resource. The domain list is fetched once and cached for the client's lifetime.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender address, e.g. `DoNotReply@contoso.com`.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<bool>` - Whether the sender's domain is linked and provisioned.
+    pub async fn verify_sender_domain(&self, sender: &str) -> EmailResult<bool> {
+        let domain = sender_domain(sender)?;
+        let resource_id = self
+            .inner
+            .email_service_resource_id
+            .as_deref()
+            .ok_or_else(|| {
+                AcsError::Client(
+                    "An email service resource ID is required to verify sender domains".to_string(),
+                )
+            })?;
+        let domains = self
+            .inner
+            .sender_domains
+            .get_or_try_init(|| list_sender_domains(&self.inner, resource_id))
+            .await?;
+        Ok(domains.iter().any(|d| d.eq_ignore_ascii_case(domain)))
+    }
+
+    // The send URL, including any extra query parameters, with the given or the default API version
+    fn send_email_url(&self, api_version: Option<&str>) -> String {
+        get_send_email_url(
+            &self.inner.host,
+            api_version.unwrap_or(API_VERSION),
+            &self.inner.extra_query,
+        )
+    }
+
+    /// Fill in the default sender if the email's sender is blank, and reject the email locally
+    /// if sender domain checks are enabled and its domain is malformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<Cow<SentEmail>>` - The email to send, or an error describing a missing or
+    ///   malformed sender address.
+    fn prepare_sender<'a>(&self, email: &'a SentEmail) -> EmailResult<Cow<'a, SentEmail>> {
+        let email = if email.sender.trim().is_empty() {
+            let sender = self.inner.default_sender.clone().ok_or_else(|| {
+                AcsError::Client(
+                    "Sender is required, set it on the email or with ACSClientBuilder::default_sender"
+                        .to_string(),
+                )
+            })?;
+            Cow::Owned(SentEmail {
+                sender,
+                ..email.clone()
+            })
+        } else {
+            Cow::Borrowed(email)
+        };
+        if self.inner.check_sender_domain {
+            sender_domain(&email.sender)?;
+        }
+        Ok(email)
+    }
+}
+
+// Spawning and status polling depend on tokio and `Instant`, neither of which work on wasm32
+#[cfg(not(target_arch = "wasm32"))]
+impl ACSClient {
+    /// Sends an email using the ACS client and periodically checks the status, invoking a callback function with the status.
+    ///
+    /// Polling stops on a terminal status, on a non-transient error, or after `STATUS_POLL_TIMEOUT`;
+    /// the returned receiver is notified exactly once in every case.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    /// * `call_back` - A callback function that takes the message ID, email send status, and optional error details.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+    pub async fn send_email_with_callback<F>(
+        self,
+        email: &SentEmail,
+        call_back: F,
+    ) -> EmailResult<(String, oneshot::Receiver<()>)>
+    where
+        F: Fn(String, &EmailSendStatusType, Option<ErrorDetail>) + Send + Sync + 'static,
+    {
+        let email = self.prepare_sender(email)?;
+        let request_id = format!("{}", Uuid::new_v4());
+        let result = acs_send_email(
+            &self.inner,
+            &self.send_email_url(None),
+            request_id.as_str(),
+            &email,
+        )
+        .await?;
+
+        let message_id = result.clone();
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            // Signals the receiver when the task exits, whichever path it takes
+            let _signal = CompletionSignal(Some(tx));
+            let resp_status = self
+                .poll_until_terminal(
+                    &message_id,
+                    STATUS_POLL_INTERVAL,
+                    STATUS_POLL_TIMEOUT,
+                    |status| {
+                        call_back(message_id.clone(), &status, None);
+                        std::future::ready(())
+                    },
+                )
+                .await;
+            if let Err(e) = resp_status {
+                call_back(
+                    message_id.clone(),
+                    &EmailSendStatusType::Unknown,
+                    Some(to_status_error_detail(e)),
+                );
+            }
+        });
+
+        Ok((result, rx))
+    }
+
+    /// Sends an email using the ACS client and periodically checks the status, awaiting an async callback with the status.
+    ///
+    /// The callback is awaited inside the polling loop, so a slow callback delays the next poll;
+    /// offload heavy work (e.g. to a spawned task or a queue) to keep polling on schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    /// * `call_back` - An async callback function that takes the message ID, email send status, and optional error details.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+    pub async fn send_email_with_async_callback<F, Fut>(
+        self,
+        email: &SentEmail,
+        call_back: F,
+    ) -> EmailResult<(String, oneshot::Receiver<()>)>
+    where
+        F: Fn(String, EmailSendStatusType, Option<ErrorDetail>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let email = self.prepare_sender(email)?;
+        let request_id = format!("{}", Uuid::new_v4());
+        let result = acs_send_email(
+            &self.inner,
+            &self.send_email_url(None),
+            request_id.as_str(),
+            &email,
+        )
+        .await?;
+
+        let message_id = result.clone();
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            // Signals the receiver when the task exits, whichever path it takes
+            let _signal = CompletionSignal(Some(tx));
+            let resp_status = self
+                .poll_until_terminal(
+                    &message_id,
+                    STATUS_POLL_INTERVAL,
+                    STATUS_POLL_TIMEOUT,
+                    |status| call_back(message_id.clone(), status, None),
+                )
+                .await;
+            if let Err(e) = resp_status {
+                call_back(
+                    message_id.clone(),
+                    EmailSendStatusType::Unknown,
+                    Some(to_status_error_detail(e)),
+                )
+                .await;
+            }
+        });
+
+        Ok((result, rx))
+    }
+
+    /// Send an email after waiting for the given delay.
+    ///
+    /// The ACS API version targeted by this client has no send time field, so the deferral
+    /// happens client-side: the process must stay alive until the delay has passed, and nothing
+    /// is sent to ACS before then. With the `cancellation` feature, cancelling the client's token
+    /// during the delay returns `AcsError::Cancelled` without sending. For durable scheduling,
+    /// persist a `QueuedEmail` with `scheduled_at` and send it from a worker instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    /// * `delay` - The time to wait before sending.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+    pub async fn send_after(&self, email: &SentEmail, delay: Duration) -> EmailResult<String> {
+        self.wait_for_next_poll(delay).await?;
+        self.send_email(email).await
+    }
+
+    /// Send an email and poll its status until it reaches a terminal status.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    /// * `interval` - The time to wait between polls.
+    /// * `timeout` - The maximum total time to keep polling, after which `AcsError::Timeout` is returned.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<(String, EmailSendStatusType)>` - The operation ID and terminal status if successful.
+    pub async fn send_and_wait(
+        &self,
+        email: &SentEmail,
+        interval: Duration,
+        timeout: Duration,
+    ) -> EmailResult<(String, EmailSendStatusType)> {
+        let message_id = self.send_email(email).await?;
+        let status = self.poll_status(&message_id, interval, timeout).await?;
+        Ok((message_id, status))
+    }
+
+    /// Send an email and poll its status until it reaches a terminal status or the deadline
+    /// passes, e.g. the deadline of the request being served.
+    ///
+    /// The send and every poll only use the time left until the deadline. If the deadline passes
+    /// while the send is in flight, ACS may still deliver the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    /// * `interval` - The time to wait between polls.
+    /// * `deadline` - When to give up with `AcsError::Timeout`, holding the time that was left
+    ///   when the call started.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<(String, EmailSendStatusType)>` - The operation ID and terminal status if successful.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::adapters::gateways::acs_email::ACSClient;
+    /// use azure_email_service::domain::entities::models::{AcsError, SentEmailBuilder};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client =
+    ///     ACSClient::try_from("endpoint=https://xxx.communication.azure.com/;accesskey=c2VjcmV0")
+    ///         .unwrap();
+    /// let email = SentEmailBuilder::new()
+    ///     .sender("donotreply@example.com".to_string())
+    ///     .subject("Subject")
+    ///     .plain_text("Body")
+    ///     .to("jane@example.com")
+    ///     .build()
+    ///     .unwrap();
+    /// // A deadline that has already passed fails before anything is sent
+    /// let result = client
+    ///     .send_and_wait_until(&email, Duration::from_secs(5), Instant::now())
+    ///     .await;
+    /// assert!(matches!(result, Err(AcsError::Timeout(_))));
+    /// # }
+    /// ```
+    pub async fn send_and_wait_until(
+        &self,
+        email: &SentEmail,
+        interval: Duration,
+        deadline: Instant,
+    ) -> EmailResult<(String, EmailSendStatusType)> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if timeout.is_zero() {
+            return Err(AcsError::Timeout(timeout));
+        }
+        let send_and_poll = async {
+            let message_id = self.send_email(email).await?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            self.poll_status(&message_id, interval, remaining)
+                .await
+                .map(|status| (message_id, status))
+        };
+        match tokio::time::timeout(timeout, send_and_poll).await {
+            Ok(Err(AcsError::Timeout(_))) | Err(_) => Err(AcsError::Timeout(timeout)),
+            Ok(result) => result,
+        }
+    }
+
+    /// Send many emails, at most `concurrency` at a time.
+    ///
+    /// Each email is sent like `send_email`, with its own request ID. A failure for one email
+    /// doesn't stop the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `emails` - The emails to send.
+    /// * `concurrency` - The maximum number of emails sent at once, at least 1.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<EmailResult<String>>` - The operation ID or error for each email, in order.
+    pub async fn send_batch(
+        &self,
+        emails: &[SentEmail],
+        concurrency: usize,
+    ) -> Vec<EmailResult<String>> {
+        let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, email) in emails.iter().enumerate() {
+            let client = self.clone();
+            let permits = permits.clone();
+            let email = email.clone();
+            tasks.spawn(async move {
+                // The semaphore is never closed, so acquiring only waits for a free permit
+                let _permit = permits.acquire_owned().await.ok();
+                (index, client.send_email(&email).await)
+            });
+        }
+
+        let mut results: Vec<Option<EmailResult<String>>> = emails.iter().map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = Some(result),
+                // Keeps the debug build panics of `send_email` visible to the caller
+                Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                Err(e) => error!("Send task failed: {}", e),
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| Err(AcsError::Client("Send task failed".to_string())))
+            })
+            .collect()
+    }
+
+    /// Send the same content to each recipient as a separate email, replacing `{{key}}`
+    /// placeholders with the recipient's values.
+    ///
+    /// The emails are sent from the client's default sender, see `ACSClientBuilder::default_sender`,
+    /// with `send_batch` and `DEFAULT_SEND_CONCURRENCY`. A failure for one recipient doesn't stop
+    /// the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The subject and bodies, with placeholders as described in `EmailContent::personalize`.
+    /// * `recipients` - Each recipient with its placeholder values.
+    /// * `strict` - Whether a placeholder without a value fails that recipient's email, rather than being sent as is.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<Vec<EmailResult<String>>>` - The operation ID or error for each recipient, in
+    ///   order, or an error before any email is sent if the client has no default sender.
+    pub async fn send_personalized(
+        &self,
+        template: &EmailContent,
+        recipients: &[(EmailAddress, HashMap<String, String>)],
+        strict: bool,
+    ) -> EmailResult<Vec<EmailResult<String>>> {
+        if self.inner.default_sender.is_none() {
+            return Err(AcsError::Client(
+                "Sender is required, set it with ACSClientBuilder::default_sender".to_string(),
+            ));
+        }
+        // The sender is left blank and filled in with the default sender when sending
+        let emails: Vec<EmailResult<SentEmail>> = recipients
+            .iter()
+            .map(|(recipient, values)| {
+                template
+                    .personalize(values, strict)
+                    .and_then(|content| {
+                        SentEmailBuilder::new()
+                            .content(content)
+                            .to(recipient.clone())
+                            .build()
+                            .map_err(|e| e.to_string())
+                    })
+                    .map_err(AcsError::Client)
+            })
+            .collect();
+
+        let batch: Vec<SentEmail> = emails
+            .iter()
+            .filter_map(|email| email.as_ref().ok().cloned())
+            .collect();
+        let mut sent = self
+            .send_batch(&batch, DEFAULT_SEND_CONCURRENCY)
+            .await
+            .into_iter();
+        Ok(emails
+            .into_iter()
+            .map(|email| match email {
+                Ok(_) => sent
+                    .next()
+                    .expect("send_batch returns one result per email"),
+                Err(e) => Err(e),
+            })
+            .collect())
+    }
+
+    /// Poll the status of many sent emails until each reaches a terminal status.
+    ///
+    /// At most `concurrency` operations are polled at a time, so a large batch doesn't exceed
+    /// the ACS rate limits. Each operation is polled like `poll_status`.
+    ///
+    /// Each ID maps to an `EmailResult` rather than a bare `EmailSendStatusType`, so a poll
+    /// that fails or times out is reported for its own ID without dropping the other statuses.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The operation IDs to poll.
+    /// * `interval` - The time to wait between polls of the same operation.
+    /// * `timeout` - The maximum total time to keep polling each operation.
+    /// * `concurrency` - The maximum number of operations polled at once, at least 1.
+    ///
+    /// # Returns
+    ///
+    /// * `HashMap<String, EmailResult<EmailSendStatusType>>` - The terminal status or error of each operation.
+    pub async fn poll_many(
+        &self,
+        ids: &[String],
+        interval: Duration,
+        timeout: Duration,
+        concurrency: usize,
+    ) -> HashMap<String, EmailResult<EmailSendStatusType>> {
+        let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for id in ids {
+            let client = self.clone();
+            let permits = permits.clone();
+            let id = id.clone();
+            tasks.spawn(async move {
+                // The semaphore is never closed, so acquiring only waits for a free permit
+                let _permit = permits.acquire_owned().await.ok();
+                let status = client.poll_status(&id, interval, timeout).await;
+                (id, status)
+            });
+        }
+
+        let mut statuses = HashMap::with_capacity(ids.len());
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((id, status)) => {
+                    statuses.insert(id, status);
+                }
+                Err(e) => error!("Status polling task failed: {}", e),
+            }
+        }
+        statuses
+    }
+
+    /// Poll the status of a sent email until it reaches a terminal status.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_id` - A reference to the message ID string.
+    /// * `interval` - The time to wait between polls.
+    /// * `timeout` - The maximum total time to keep polling, after which `AcsError::Timeout` is returned.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<EmailSendStatusType>` - The terminal status if successful.
+    pub async fn poll_status(
+        &self,
+        message_id: &str,
+        interval: Duration,
+        timeout: Duration,
+    ) -> EmailResult<EmailSendStatusType> {
+        self.poll_until_terminal(message_id, interval, timeout, |_| std::future::ready(()))
+            .await
+    }
+
+    /// Poll the status of a sent email until it reaches a terminal status.
+    ///
+    /// There is always at least one poll, and the last wait is cut short at the deadline, so an
+    /// `interval` longer than `timeout` still polls once. Connection failures and request timeouts
+    /// are treated as transient and retried up to `MAX_TRANSIENT_POLL_ERRORS` times in a row, and
+    /// an empty response is polled again. Other errors, e.g. an undecodable status, end polling
+    /// immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_id` - A reference to the message ID string.
+    /// * `interval` - The time to wait between polls.
+    /// * `timeout` - The maximum total time to keep polling.
+    /// * `on_status` - An async function awaited with every status received.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<EmailSendStatusType>` - The terminal status if successful.
+    async fn poll_until_terminal<F, Fut>(
+        &self,
+        message_id: &str,
+        interval: Duration,
+        timeout: Duration,
+        mut on_status: F,
+    ) -> EmailResult<EmailSendStatusType>
+    where
+        F: FnMut(EmailSendStatusType) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let deadline = Instant::now() + timeout;
+        let mut transient_errors = 0;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            self.wait_for_next_poll(interval.min(remaining)).await?;
+            match self.get_email_status(message_id).await {
+                Ok(status) => {
+                    debug!("Email status: {}", status);
+                    transient_errors = 0;
+                    on_status(status).await;
+                    if status.is_terminal() {
+                        return Ok(status);
+                    }
+                }
+                Err(AcsError::EmptyResponse { status }) => {
+                    debug!("Email status not available yet (HTTP {})", status);
+                }
+                Err(e @ (AcsError::Connect(_) | AcsError::RequestTimeout(_)))
+                    if transient_errors < MAX_TRANSIENT_POLL_ERRORS =>
+                {
+                    transient_errors += 1;
+                    debug!(
+                        "Transient error getting email status ({}/{}): {}",
+                        transient_errors, MAX_TRANSIENT_POLL_ERRORS, e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+            if Instant::now() >= deadline {
+                return Err(AcsError::Timeout(timeout));
+            }
+        }
+    }
+
+    /// Wait for the next status poll, returning early if the client's cancellation token fires.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time to wait before the next poll.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<()>` - `AcsError::Cancelled` if polling was cancelled.
+    async fn wait_for_next_poll(&self, interval: Duration) -> EmailResult<()> {
+        #[cfg(feature = "cancellation")]
+        if let Some(token) = &self.inner.cancellation_token {
+            return tokio::select! {
+                _ = delay(interval) => Ok(()),
+                _ = token.cancelled() => Err(AcsError::Cancelled),
+            };
+        }
+        delay(interval).await;
+        Ok(())
     }
 }
 
 async fn send_request<T>(
+    inner: &ACSClientInner,
     method: reqwest::Method,
     url: &str,
     request_id: &str,
     body: Option<&T>,
-    acs_auth_method: &ACSAuthMethod,
 ) -> EmailResult<reqwest::Response>
+where
+    T: serde::Serialize,
+{
+    let prepared = prepare_request(inner, method, url, request_id, body).await?;
+    let request_builder = inner
+        .http_client
+        .request(prepared.method, prepared.url)
+        .headers(prepared.headers);
+    // Send the exact bytes that were hashed for the signature
+    let request_builder = if body.is_some() {
+        request_builder.body(prepared.body)
+    } else {
+        request_builder
+    };
+    let request = request_builder.build().map_err(to_transport_error)?;
+    if let Some(interceptor) = &inner.interceptor {
+        interceptor.before_send(&request);
+    }
+    let response = inner
+        .http_client
+        .execute(request)
+        .await
+        .map_err(to_transport_error)?;
+    if let Some(interceptor) = &inner.interceptor {
+        interceptor.after_receive(&response);
+    }
+    Ok(response)
+}
+
+/// Serialize the body and create the signed headers for a request without sending it.
+///
+/// # Arguments
+///
+/// * `inner` - The client state, with the authentication method and cached access token.
+/// * `method` - The HTTP method of the request.
+/// * `url` - The URL to send the request to.
+/// * `request_id` - The request ID string.
+/// * `body` - An optional reference to the request body.
+///
+/// # Returns
+///
+/// * `EmailResult<PreparedRequest>` - The prepared request if successful.
+async fn prepare_request<T>(
+    inner: &ACSClientInner,
+    method: reqwest::Method,
+    url: &str,
+    request_id: &str,
+    body: Option<&T>,
+) -> EmailResult<PreparedRequest>
 where
     T: serde::Serialize,
 {
     let url_endpoint = parse_url(url)?;
-    let client = Client::new();
     let json_body = serialize_body(body)?;
     let headers = create_headers(
+        inner,
         &url_endpoint,
         method.as_str(),
         request_id,
         &json_body,
-        acs_auth_method,
     )
     .await?;
-    let request_builder = client.request(method, url).headers(headers);
-    let request_builder = if let Some(body) = body {
-        request_builder.json(body)
+    Ok(PreparedRequest {
+        method,
+        // Send the normalized URL that was signed, not the caller's string
+        url: url_endpoint.to_string(),
+        headers,
+        body: json_body,
+    })
+}
+
+fn parse_url(url: &str) -> EmailResult<Url> {
+    Url::parse(url).map_err(|e| to_error_response("Invalid URL", e))
+}
+
+fn serialize_body<T: serde::Serialize>(body: Option<&T>) -> EmailResult<String> {
+    if let Some(body) = body {
+        serde_json::to_string(body)
+            .map_err(|e| to_error_response("Failed to serialize request body", e))
     } else {
-        request_builder
-    };
-    request_builder
-        .send()
+        // Requests without a body, e.g. status GETs, are signed over empty content
+        Ok(String::new())
+    }
+}
+
+// Wrap the client's `reqwest::Client` for `azure_identity`, so token requests share its TLS settings
+fn create_http_client(http_client: &Client) -> Arc<dyn HttpClient> {
+    Arc::new(http_client.clone()) as Arc<dyn HttpClient>
+}
+
+/// Get the token credential of the client, creating it on first use.
+///
+/// The credential is shared by all requests of the client and its clones, so its own token cache
+/// is reused too.
+///
+/// # Arguments
+///
+/// * `inner` - The client state, with the authentication method.
+///
+/// # Returns
+///
+/// * `Result<Arc<dyn TokenCredential>, String>` - The credential, or an error for shared key
+///   authentication or when no managed identity credential is available.
+async fn token_credential(inner: &ACSClientInner) -> Result<Arc<dyn TokenCredential>, String> {
+    inner
+        .credential
+        .get_or_try_init(|| async {
+            match &inner.auth_method {
+                ACSAuthMethod::ServicePrincipal {
+                    tenant_id,
+                    client_id,
+                    client_secret,
+                    cloud,
+                } => {
+                    // Use Azure AD client credential flow (requires async-http-client support)
+                    let http_client = create_http_client(&inner.http_client);
+                    let token_url = cloud.authority_host();
+                    debug!("Token URL: {}", token_url);
+                    debug!("Creating client secret credential");
+                    debug!("Client ID: {}", client_id);
+                    debug!("Tenant ID: {}", tenant_id);
+                    let token_url = Url::parse(token_url)
+                        .map_err(|e| format!("Invalid authority host: {}", e))?;
+                    Ok(Arc::new(ClientSecretCredential::new(
+                        http_client,
+                        token_url,
+                        tenant_id.to_string(),
+                        client_id.to_string(),
+                        client_secret.to_string(),
+                    )) as Arc<dyn TokenCredential>)
+                }
+                ACSAuthMethod::ManagedIdentity { .. } => create_credential().map_err(|e| {
+                    format!(
+                        "Failed to create credential: {} ({})",
+                        e, MANAGED_IDENTITY_HINT
+                    )
+                }),
+                ACSAuthMethod::TokenCredential { credential, .. } => Ok(credential.clone()),
+                ACSAuthMethod::SharedKey(_) => {
+                    Err("Shared key authentication doesn't use access tokens".to_string())
+                }
+            }
+        })
         .await
-        .map_err(|e| to_error_response("Request failed", e))
+        .cloned()
 }
 
-fn parse_url(url: &str) -> EmailResult<Url> {
-    Url::parse(url).map_err(|e| to_error_response("Invalid URL", e))
+/// Request a new access token for the given scope from the client's credential.
+///
+/// # Arguments
+///
+/// * `inner` - The client state, with the authentication method.
+/// * `scope` - The scope to request the token for.
+///
+/// # Returns
+///
+/// * `Result<AccessToken, String>` - The result of the token acquisition, containing the token if successful.
+async fn request_access_token(inner: &ACSClientInner, scope: &str) -> Result<AccessToken, String> {
+    let credential = token_credential(inner).await?;
+    let scopes = [scope];
+    match &inner.auth_method {
+        ACSAuthMethod::ManagedIdentity { token_timeout, .. } => {
+            // Off Azure the identity endpoint doesn't exist, and probing it can take a long time
+            tokio::select! {
+                biased;
+                token = credential.get_token(&scopes) => token.map_err(|e| {
+                    format!("Failed to get access token: {} ({})", e, MANAGED_IDENTITY_HINT)
+                }),
+                _ = delay(*token_timeout) => Err(format!(
+                    "Timed out after {:?} getting a managed identity token ({})",
+                    token_timeout, MANAGED_IDENTITY_HINT
+                )),
+            }
+        }
+        _ => credential
+            .get_token(&scopes)
+            .await
+            .map_err(|e| format!("Failed to get access token: {}", e)),
+    }
+}
+
+/// Get an access token for the ACS data plane of the client's cloud.
+///
+/// The token is cached on the client and reused until `TOKEN_REFRESH_MARGIN` before it expires,
+/// so concurrent sends wait for a single token request instead of each acquiring their own.
+///
+/// # Arguments
+///
+/// * `inner` - The client state, with the authentication method and cached access token.
+///
+/// # Returns
+///
+/// * `Result<String, String>` - The result of the token acquisition, containing the token if successful.
+async fn get_access_token(inner: &ACSClientInner) -> Result<String, String> {
+    let mut cached = inner.access_token.lock().await;
+    if let Some(cached) = cached
+        .as_ref()
+        .filter(|cached| now() + TOKEN_REFRESH_MARGIN < cached.expires_on)
+    {
+        return Ok(cached.token.clone());
+    }
+    let scope = inner.auth_method.cloud().communication_scope();
+    let token = request_access_token(inner, scope).await?;
+    let secret = token.token.secret().to_owned();
+    *cached = Some(CachedToken {
+        token: secret.clone(),
+        expires_on: SystemTime::from(token.expires_on),
+    });
+    Ok(secret)
+}
+
+/// Drop the client's cached access token, e.g. after a `401`, so the next request acquires a new one.
+///
+/// The credential's own token cache is cleared too, otherwise it would hand out the same token.
+///
+/// # Arguments
+///
+/// * `inner` - The client state, with the cached access token and credential.
+async fn clear_access_token(inner: &ACSClientInner) {
+    *inner.access_token.lock().await = None;
+    if let Some(credential) = inner.credential.get() {
+        if let Err(e) = credential.clear_cache().await {
+            debug!("Failed to clear the credential's token cache: {}", e);
+        }
+    }
+}
+
+/// Create headers for the request based on the provided authentication method.
+///
+/// # Arguments
+///
+/// * `inner` - The client state, with the authentication method and cached access token.
+/// * `url_endpoint` - A reference to the `Url` struct representing the endpoint URL.
+/// * `method` - A reference to the HTTP method string.
+/// * `request_id` - A reference to the request ID string.
+/// * `json_body` - A reference to the JSON body string.
+///
+/// # Returns
+///
+/// * `EmailResult<reqwest::header::HeaderMap>` - The result of the header creation, containing the headers if successful.
+async fn create_headers(
+    inner: &ACSClientInner,
+    url_endpoint: &Url,
+    method: &str,
+    request_id: &str,
+    json_body: &str,
+) -> EmailResult<reqwest::header::HeaderMap> {
+    // Reject a caller-supplied request ID that can't be sent, before acquiring a token
+    let request_id_value = HeaderValue::from_str(request_id)
+        .map_err(|e| to_error_response("Invalid request ID", e))?;
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    match &inner.auth_method {
+        ACSAuthMethod::SharedKey(share_key) => {
+            headers =
+                SharedKeySigner::new(share_key).sign(method, url_endpoint, request_id, json_body)?
+        }
+        ACSAuthMethod::ServicePrincipal { .. }
+        | ACSAuthMethod::ManagedIdentity { .. }
+        | ACSAuthMethod::TokenCredential { .. } => {
+            let token = get_access_token(inner)
+                .await
+                .map_err(|e| to_error_response("Failed to acquire access token", e))?;
+            let mut authorization = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|e| to_error_response("Invalid access token", e))?;
+            authorization.set_sensitive(true);
+            headers.insert(reqwest::header::AUTHORIZATION, authorization);
+            headers.insert(
+                reqwest::header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            headers.insert(
+                reqwest::header::HeaderName::from_static("x-ms-client-request-id"),
+                request_id_value.clone(),
+            );
+            headers.insert(
+                reqwest::header::HeaderName::from_static("repeatability-request-id"),
+                request_id_value,
+            );
+            headers.insert(
+                reqwest::header::HeaderName::from_static("repeatability-first-sent"),
+                HeaderValue::from_str(&fmt_http_date(now()))
+                    .map_err(|e| to_error_response("Invalid date header", e))?,
+            );
+        }
+    }
+
+    debug!("Headers: {:#?}", headers);
+    Ok(headers)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Convert a status polling error into the `ErrorDetail` passed to status callbacks.
+///
+/// # Arguments
+///
+/// * `error` - The `AcsError` that ended polling.
+///
+/// # Returns
+///
+/// * `ErrorDetail` - The API error details, or a detail wrapping the client error message.
+fn to_status_error_detail(error: AcsError) -> ErrorDetail {
+    match error {
+        AcsError::Api { response, .. } if response.error.is_some() => {
+            response.error.unwrap_or_default()
+        }
+        e => ErrorDetail {
+            message: Some(format!("Error getting email status: {}", e)),
+            ..Default::default()
+        },
+    }
+}
+
+/// Convert an error into an `AcsError`.
+///
+/// # Arguments
+///
+/// * `message` - A reference to the error message string.
+/// * `error` - An object that implements the `ToString` trait.
+///
+/// # Returns
+///
+/// * `AcsError` - The client error containing the error message.
+fn to_error_response(message: &str, error: impl ToString) -> AcsError {
+    AcsError::Client(format!("{}: {}", message, error.to_string()))
+}
+
+/// Convert a `reqwest::Error` into an `AcsError` that tells the failure kinds apart.
+///
+/// # Arguments
+///
+/// * `error` - The error returned by `reqwest`.
+///
+/// # Returns
+///
+/// * `AcsError` - A `Connect`, `RequestTimeout`, `Decode` or `Api` error, or a `Client` error
+///   for other failures. The message includes the error's sources, e.g. the DNS or TLS cause.
+fn to_transport_error(error: reqwest::Error) -> AcsError {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+
+    if error.is_timeout() {
+        AcsError::RequestTimeout(message)
+    } else if error.is_connect() {
+        AcsError::Connect(message)
+    } else if error.is_decode() || error.is_body() {
+        AcsError::Decode(message)
+    } else if let Some(status) = error.status().filter(|_| error.is_status()) {
+        AcsError::api(
+            Some(status.as_u16()),
+            ErrorResponse {
+                error: Some(ErrorDetail {
+                    message: Some(message),
+                    ..Default::default()
+                }),
+            },
+        )
+    } else {
+        AcsError::Client(format!("Request failed: {}", message))
+    }
+}
+
+/// Get the operation of a sent email using the ACS client.
+///
+/// # Arguments
+///
+/// * `inner` - The client state, with the host, credentials and extra query parameters.
+/// * `request_id` - The operation ID, or the full operation URL from an `Operation-Location` header.
+/// * `location` - The `Operation-Location` URL returned for the operation, if any, polled instead
+///   of the URL built from the ID.
+/// * `api_version` - An API version overriding the default and any version of an operation URL.
+///
+/// # Returns
+///
+/// * `EmailResult<SentEmailResponse>` - The parsed operation, including any embedded error details.
+async fn acs_get_email_operation(
+    inner: &ACSClientInner,
+    request_id: &str,
+    location: Option<&str>,
+    api_version: Option<&str>,
+) -> EmailResult<SentEmailResponse> {
+    let host_name = inner.host.as_str();
+    let mut url = match location {
+        Some(location) => {
+            let mut url = parse_url(location)?;
+            if let Some(api_version) = api_version {
+                set_api_version(&mut url, api_version);
+            }
+            url
+        }
+        None => match Url::parse(request_id) {
+            Ok(url) if matches!(url.scheme(), "https" | "http") => {
+                operation_url_for_host(url, host_name, api_version)?
+            }
+            _ => {
+                let mut url = parse_url(&format!(
+                    "{}/emails/operations",
+                    endpoint_base_url(host_name)
+                ))?;
+                // Push the operation ID as a segment, so characters like `/` or `?` are percent-encoded
+                url.path_segments_mut()
+                    .map_err(|_| to_error_response("Invalid URL", host_name))?
+                    .push(request_id);
+                url.query_pairs_mut()
+                    .append_pair("api-version", api_version.unwrap_or(API_VERSION));
+                url
+            }
+        },
+    };
+    if !inner.extra_query.is_empty() {
+        url.query_pairs_mut().extend_pairs(&inner.extra_query);
+    }
+    let request_id = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or(request_id)
+        .to_string();
+    let url = url.to_string();
+    debug!("end point URL: {}", url);
+
+    let response = send_request::<()>(inner, reqwest::Method::GET, &url, &request_id, None).await?;
+    // A `204 No Content` surfaces as `AcsError::EmptyResponse`, the status isn't available yet
+    if matches!(response.status(), StatusCode::OK | StatusCode::NO_CONTENT) {
+        parse_response::<SentEmailResponse>(response).await
+    } else {
+        Err(to_api_error(response).await)
+    }
 }
 
-fn serialize_body<T: serde::Serialize>(body: Option<&T>) -> EmailResult<String> {
-    if let Some(body) = body {
-        serde_json::to_string(body)
-            .map_err(|e| to_error_response("Failed to serialize request body", e))
-    } else {
-        Ok(String::new())
+/// Check that a full operation URL points at the client's endpoint and has an API version.
+///
+/// The request is signed or carries a bearer token, so it must never be sent to another host,
+/// port or scheme.
+///
+/// # Arguments
+///
+/// * `url` - The operation URL, e.g. from an `Operation-Location` header.
+/// * `host_name` - A reference to the host name string of the client.
+/// * `api_version` - An API version replacing the URL's, if any.
+///
+/// # Returns
+///
+/// * `EmailResult<Url>` - The URL, with the overriding `api-version`, or the default one
+///   appended if it was missing.
+fn operation_url_for_host(
+    mut url: Url,
+    host_name: &str,
+    api_version: Option<&str>,
+) -> EmailResult<Url> {
+    let endpoint = endpoint_base_url(host_name);
+    if !is_same_origin(url.as_str(), &endpoint) {
+        return Err(AcsError::Client(format!(
+            "Operation URL '{}' doesn't point at the client endpoint '{}'",
+            url, endpoint
+        )));
     }
+    if let Some(api_version) = api_version {
+        set_api_version(&mut url, api_version);
+    } else if !url.query_pairs().any(|(name, _)| name == "api-version") {
+        url.query_pairs_mut()
+            .append_pair("api-version", API_VERSION);
+    }
+    Ok(url)
 }
 
-// Adding a function to create a `HttpClient`
-fn create_http_client() -> Arc<dyn HttpClient> {
-    // Assuming `request` is used as the HTTP client
-    Arc::new(Client::new()) as Arc<dyn HttpClient>
+// Replace the `api-version` of a URL, keeping it first and the other parameters in order
+fn set_api_version(url: &mut Url, api_version: &str) {
+    let query: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| name != "api-version")
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .append_pair("api-version", api_version)
+        .extend_pairs(query);
 }
 
-/// Get an access token based on the provided authentication method.
+/// Extract the domain of a sender address, rejecting obviously malformed domains.
 ///
 /// # Arguments
 ///
-/// * `auth_method` - A reference to the `ACSAuthMethod` enum specifying the authentication method.
+/// * `sender` - The sender address, bare or as `DoNotReply <donotreply@example.com>`.
 ///
 /// # Returns
 ///
-/// * `Result<String, String>` - The result of the token acquisition, containing the token if successful.
-async fn get_access_token(auth_method: &ACSAuthMethod) -> Result<String, String> {
-    match auth_method {
-        ACSAuthMethod::ServicePrincipal {
-            tenant_id,
-            client_id,
-            client_secret,
-        } => {
-            // Use Azure AD client credential flow (requires async-http-client support)
-            let http_client = create_http_client();
-            /*
-            let token_url = format!(
-                "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
-                tenant_id
-            );*/
-            let token_url = "https://login.microsoftonline.com/";
-            debug!("Token URL: {}", token_url);
-            debug!("Creating client secret credential");
-            debug!("Client ID: {}", client_id);
-            debug!("Client Secret: {}", client_secret);
-            debug!("Tenant ID: {}", tenant_id);
-            let credential = ClientSecretCredential::new(
-                http_client,
-                Url::parse(&token_url).unwrap(),
-                tenant_id.to_string(),
-                client_id.to_string(),
-                client_secret.to_string(),
-            );
-            let token = credential
-                .get_token(&["https://communication.azure.com/.default"])
-                .await
-                .map_err(|e| format!("Failed to get access token: {}", e))?;
-            debug!("Access token: {:#?}", token);
-
-            return Ok(token.token.secret().to_owned());
-        }
-        ACSAuthMethod::ManagedIdentity => {
-            let credential =
-                create_credential().map_err(|e| format!("Failed to create credential: {}", e))?;
-            let token = credential
-                .get_token(&["https://communication.azure.com/.default"])
-                .await
-                .map_err(|e| format!("Failed to get access token: {}", e))?;
-            return Ok(token.token.secret().to_owned());
-        }
-        _ => {}
+/// * `EmailResult<&str>` - The domain of the sender address.
+fn sender_domain(sender: &str) -> EmailResult<&str> {
+    let invalid = |reason: &str| {
+        AcsError::Client(format!(
+            "Sender address '{}' has an invalid domain: {}",
+            sender, reason
+        ))
+    };
+    let address = sender
+        .trim()
+        .strip_suffix('>')
+        .and_then(|rest| rest.rsplit_once('<'))
+        .map_or(sender, |(_, address)| address);
+    let (_, domain) = address
+        .rsplit_once('@')
+        .ok_or_else(|| invalid("missing '@'"))?;
+    if !domain.contains('.') {
+        return Err(invalid("expected a dot-separated domain name"));
     }
-    Ok("".to_string())
+    let labels_valid = domain.split('.').all(|label| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    if !labels_valid {
+        return Err(invalid(
+            "labels must be non-empty and only contain letters, digits and inner hyphens",
+        ));
+    }
+    Ok(domain)
 }
 
-/// Create headers for the request based on the provided authentication method.
+/// List the provisioned sender domains of an Email Communication Service resource.
 ///
 /// # Arguments
 ///
-/// * `url_endpoint` - A reference to the `Url` struct representing the endpoint URL.
-/// * `method` - A reference to the HTTP method string.
-/// * `request_id` - A reference to the request ID string.
-/// * `json_body` - A reference to the JSON body string.
-/// * `auth_method` - A reference to the `ACSAuthMethod` enum specifying the authentication method.
+/// * `inner` - The client state, with the HTTP client and credentials.
+/// * `resource_id` - The ARM resource ID of the Email Communication Service.
 ///
 /// # Returns
 ///
-/// * `EmailResult<reqwest::header::HeaderMap>` - The result of the header creation, containing the headers if successful.
-async fn create_headers(
-    url_endpoint: &Url,
-    method: &str,
-    request_id: &str,
-    json_body: &str,
-    auth_method: &ACSAuthMethod,
-) -> EmailResult<reqwest::header::HeaderMap> {
-    let mut headers = reqwest::header::HeaderMap::new();
+/// * `EmailResult<Vec<String>>` - The sender domains whose provisioning has succeeded.
+async fn list_sender_domains(
+    inner: &ACSClientInner,
+    resource_id: &str,
+) -> EmailResult<Vec<String>> {
+    if let ACSAuthMethod::SharedKey(_) = inner.auth_method {
+        return Err(AcsError::Client(
+            "Verifying sender domains requires service principal, managed identity or token credential authentication"
+                .to_string(),
+        ));
+    }
+    let cloud = inner.auth_method.cloud();
+    let management_scope = format!("{}.default", cloud.management_endpoint());
+    // The domain list is fetched once per client, so the management token isn't cached
+    let token = request_access_token(inner, &management_scope)
+        .await
+        .map_err(|e| to_error_response("Failed to acquire access token", e))?;
+    let token = token.token.secret();
 
-    match auth_method {
-        ACSAuthMethod::SharedKey(share_key) => {
-            headers = get_request_header(url_endpoint, method, request_id, json_body, share_key)
-                .map_err(|e| to_error_response("Header creation failed", e))?
-        }
-        ACSAuthMethod::ServicePrincipal { .. } | ACSAuthMethod::ManagedIdentity => {
-            let token = get_access_token(auth_method)
-                .await
-                .map_err(|e| to_error_response("Failed to acquire access token", e))?;
-            headers.insert(
-                reqwest::header::AUTHORIZATION,
-                format!("Bearer {}", token).parse().unwrap(),
-            );
-            headers.insert(
-                reqwest::header::CONTENT_TYPE,
-                "application/json".parse().unwrap(),
-            );
-            headers.insert(
-                reqwest::header::HeaderName::from_static("x-ms-client-request-id"),
-                request_id.parse().unwrap(),
-            );
+    let mut domains = Vec::new();
+    let mut next_url = Some(format!(
+        "{}{}/domains?api-version={}",
+        cloud.management_endpoint(),
+        resource_id.trim_start_matches('/'),
+        DOMAINS_API_VERSION
+    ));
+    while let Some(url) = next_url {
+        debug!("end point URL: {}", url);
+        let response = inner
+            .http_client
+            .get(&url)
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(to_transport_error)?;
+        if response.status() != StatusCode::OK {
+            return Err(to_api_error(response).await);
         }
+        let page = parse_response::<DomainResourceList>(response).await?;
+        domains.extend(
+            page.value
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|domain| domain.properties)
+                .filter(|properties| properties.provisioning_state.as_deref() == Some("Succeeded"))
+                .filter_map(|properties| properties.from_sender_domain),
+        );
+        next_url = page.next_link;
     }
+    Ok(domains)
+}
 
-    debug!("Headers: {:#?}", headers);
-    Ok(headers)
+/// Log the pretty-printed JSON body of an email at debug level.
+///
+/// Recipient addresses are masked unless the `log-pii` feature is enabled. Nothing is serialized
+/// when debug logging is disabled.
+///
+/// # Arguments
+///
+/// * `email` - A reference to the `SentEmail` struct containing the email details.
+fn log_request_body(email: &SentEmail) {
+    if !log_enabled!(Level::Debug) {
+        return;
+    }
+    #[cfg(feature = "log-pii")]
+    let body = serde_json::to_string_pretty(email);
+    #[cfg(not(feature = "log-pii"))]
+    let body = serde_json::to_string_pretty(&email.redacted());
+    match body {
+        Ok(body) => debug!("Request body:\n{}", body),
+        Err(e) => debug!("Failed to serialize request body for logging: {}", e),
+    }
 }
 
-/// Convert an error into an `ErrorResponse`.
+/// Derive a repeatability request ID from an idempotency key.
+///
+/// ACS expects the request ID to be a UUID, so the first 16 bytes of the key's SHA-256 digest
+/// are formatted as a custom (version 8) UUID.
 ///
 /// # Arguments
 ///
-/// * `message` - A reference to the error message string.
-/// * `error` - An object that implements the `ToString` trait.
+/// * `key` - The idempotency key.
 ///
 /// # Returns
 ///
-/// * `ErrorResponse` - The error response containing the error details.
-fn to_error_response(message: &str, error: impl ToString) -> ErrorResponse {
-    ErrorResponse {
-        error: Some(ErrorDetail {
-            message: Some(format!("{}: {}", message, error.to_string())),
-            ..Default::default()
-        }),
-    }
+/// * `String` - The UUID request ID.
+fn idempotency_request_id(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_custom_bytes(bytes)
+        .into_uuid()
+        .to_string()
 }
 
-/// Get the status of a sent email using the ACS client.
+/// Build the URL of the send email endpoint.
 ///
 /// # Arguments
 ///
-/// * `host_name` - A reference to the host name string.
-/// * `acs_auth_method` - A reference to the `ACSAuthMethod` enum specifying the authentication method.
-/// * `request_id` - A reference to the request ID string.
+/// * `host` - A reference to the host string.
+/// * `api_version` - The `api-version` query parameter.
+/// * `extra_query` - Query parameters to append after `api-version`, in order.
 ///
 /// # Returns
 ///
-/// * `EmailResult<EmailSendStatusType>` - The result of the email status query, containing the status if successful.
-async fn acs_get_email_status(
-    host_name: &str,
-    acs_auth_method: &ACSAuthMethod,
-    request_id: &str,
-) -> EmailResult<EmailSendStatusType> {
-    let url = format!(
-        "https://{}/emails/operations/{}?api-version={}",
-        host_name.replace("https://", ""),
-        request_id,
-        API_VERSION
-    );
-    debug!("end point URL: {}", url);
-
-    let response = send_request::<()>(
-        reqwest::Method::GET,
-        &url,
-        request_id,
-        None,
-        acs_auth_method,
-    )
-    .await?;
-    if response.status() == StatusCode::OK {
-        let email_response = parse_response::<SentEmailResponse>(response).await?;
-        email_response
-            .status
-            .map(|status| Ok(status.to_type()))
-            .unwrap_or_else(|| Err(create_missing_status_error()))
-    } else {
-        let error_response = parse_response::<ErrorResponse>(response).await?;
-        Err(error_response)
-    }
+/// * `String` - The send email URL.
+fn get_send_email_url(host: &str, api_version: &str, extra_query: &[(String, String)]) -> String {
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("api-version", api_version)
+        .extend_pairs(extra_query)
+        .finish();
+    format!("{}/emails:send?{}", endpoint_base_url(host), query)
 }
 
 /// Send an email using the ACS client.
 ///
 /// # Arguments
 ///
-/// * `host` - A reference to the host string.
-/// * `acs_auth_method` - A reference to the `ACSAuthMethod` enum specifying the authentication method.
+/// * `inner` - The client state, with the credentials, retry policy, metrics and sent log.
+/// * `url` - The send URL, see `get_send_email_url`.
 /// * `request_id` - A reference to the request ID string.
 /// * `email` - A reference to the `SentEmail` struct containing the email details.
 ///
@@ -430,111 +2517,232 @@ async fn acs_get_email_status(
 ///
 /// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
 async fn acs_send_email(
-    host: &str,
-    acs_auth_method: &ACSAuthMethod,
+    inner: &ACSClientInner,
+    url: &str,
     request_id: &str,
     email: &SentEmail,
 ) -> EmailResult<String> {
-    let url = format!(
-        "https://{}/emails:send?api-version={}",
-        host.replace("https://", ""),
-        API_VERSION
-    );
-    debug!("end point URL: {}", url);
-    let response = send_request(
-        reqwest::Method::POST,
-        &url,
-        request_id,
-        Some(email),
-        acs_auth_method,
-    )
-    .await?;
-    debug!("{:#?}", response);
-    // handle response and retry if needed
-    handle_response_and_retry_if_needed(
-        response,
-        reqwest::Method::POST,
-        &url,
-        request_id,
-        Some(email),
-        acs_auth_method,
-        3,
+    // Catches emails that can never be sent early in development, without a cost in release
+    // builds, where the server rejects them instead.
+    #[cfg(debug_assertions)]
+    if let Err(e) = email.validate_structure() {
+        panic!(
+            "Invalid email passed to the ACS client: {}. Build emails with SentEmailBuilder or check them with SentEmail::validate",
+            e
+        );
+    }
+    log_request_body(email);
+    let operation_id = acs_send_body(inner, url, request_id, email).await?;
+    if let Some(sent_log) = &inner.sent_log {
+        sent_log.record(SentRecord {
+            operation_id: operation_id.clone(),
+            request_id: request_id.to_string(),
+            sent_at: now(),
+            recipient_count: email.recipient_count(),
+        });
+    }
+    Ok(operation_id)
+}
+
+/// Post a request body to the `emails:send` endpoint, retrying and recording metrics.
+///
+/// # Arguments
+///
+/// * `inner` - The client state, with the credentials, retry policy and metrics.
+/// * `url` - The send URL, see `get_send_email_url`.
+/// * `request_id` - A reference to the request ID string.
+/// * `body` - The request body, serialized to JSON.
+///
+/// # Returns
+///
+/// * `EmailResult<String>` - The result of the email send operation, containing the message ID if successful.
+async fn acs_send_body<T>(
+    inner: &ACSClientInner,
+    url: &str,
+    request_id: &str,
+    body: &T,
+) -> EmailResult<String>
+where
+    T: serde::Serialize,
+{
+    let started = now();
+    let result = async {
+        debug!("end point URL: {}", url);
+        let response =
+            send_request(inner, reqwest::Method::POST, url, request_id, Some(body)).await?;
+        debug!("{:#?}", response);
+        // handle response and retry if needed
+        handle_response_and_retry_if_needed(
+            inner,
+            response,
+            reqwest::Method::POST,
+            url,
+            request_id,
+            Some(body),
+        )
+        .await
+    }
+    .await;
+    if let Some(metrics) = &inner.metrics {
+        let status = match &result {
+            Ok(_) => StatusCode::ACCEPTED.as_u16(),
+            Err(e) => e.http_status().unwrap_or(0),
+        };
+        metrics.record_send(status, now().duration_since(started).unwrap_or_default());
+    }
+    result
+}
+
+/// Build the scheme and authority of the client's ACS endpoint.
+///
+/// Hosts are reached over HTTPS, unless the client was built with a loopback `http://` host,
+/// e.g. a local mock server.
+///
+/// # Arguments
+///
+/// * `host` - A reference to the host string.
+///
+/// # Returns
+///
+/// * `String` - The base URL, without a trailing `/`.
+fn endpoint_base_url(host: &str) -> String {
+    if host.starts_with("http://") {
+        return host.trim_end_matches('/').to_string();
+    }
+    format!(
+        "https://{}",
+        host.trim_start_matches("https://").trim_end_matches('/')
     )
-    .await
 }
+
 /// Handle the response from the email send operation and retry if needed.
 ///
+/// `429` and `503` responses are retried up to `max_retries` times, failing with
+/// `AcsError::Timeout` if the next wait would exceed the policy's total retry duration. A `401`
+/// with token based authentication clears the cached access token and is retried once with a
+/// new one; a second `401` is returned as an error.
+///
 /// # Arguments
 ///
+/// * `inner` - The client state, with the credentials, retry policy, operation locations and metrics.
 /// * `response` - The `reqwest::Response` object.
 /// * `method` - The HTTP method used for the request.
 /// * `url` - The URL to send the request to.
 /// * `request_id` - The request ID string.
 /// * `body` - An optional reference to the request body.
-/// * `acs_auth_method` - A reference to the `ACSAuthMethod` enum specifying the authentication method.
-/// * `max_retries` - The maximum number of retries.
 ///
 /// # Returns
 ///
 /// * `EmailResult<String>` - The result of the response handling, containing the message ID if successful.
 async fn handle_response_and_retry_if_needed<T>(
+    inner: &ACSClientInner,
     mut response: reqwest::Response,
     method: reqwest::Method,
     url: &str,
     request_id: &str,
     body: Option<&T>,
-    acs_auth_method: &ACSAuthMethod,
-    max_retries: u32,
 ) -> EmailResult<String>
 where
     T: serde::Serialize,
 {
+    let retry_policy = inner.retry_policy;
+    let started = now();
     let mut retries = 0;
+    let mut token_refreshed = false;
 
     loop {
         match response.status() {
             StatusCode::ACCEPTED => {
-                return parse_response::<SentEmailResponse>(response)
-                    .await?
-                    .id
-                    .ok_or_else(create_missing_id_error);
+                // Fall back to the Operation-Location header when the body has no ID or is empty
+                let location = operation_location(response.headers());
+                let location_id = location.as_deref().and_then(operation_id_from_location);
+                let result = match parse_sent_email_response(response).await {
+                    Ok(email_response) => email_response
+                        .id
+                        .or(location_id.clone())
+                        .ok_or_else(create_missing_id_error),
+                    Err(
+                        e @ (AcsError::Client(_)
+                        | AcsError::Decode(_)
+                        | AcsError::EmptyResponse { .. }),
+                    ) => location_id.clone().ok_or(e),
+                    Err(e) => Err(e),
+                };
+                // Poll the URL the service returned, but only for the same operation on the
+                // client's own endpoint, so signatures never go to a host taken from a header
+                if let (Ok(id), Some(location)) = (&result, location) {
+                    if location_id.as_ref() == Some(id) && is_same_origin(&location, url) {
+                        remember_operation_location(&inner.operation_locations, id, location);
+                    }
+                }
+                return result;
             }
             StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => {
-                if retries >= max_retries {
+                if retries >= retry_policy.max_retries {
                     error!("Max retries exceeded");
                     return parse_error_response(response).await;
                 }
+                // Logs the rate limit headers, the final response's are kept on the error instead
+                rate_limit_info(response.headers());
 
-                if let Some(retry_after) = response.headers().get(RETRY_AFTER) {
-                    if let Ok(retry_after_value) = retry_after.to_str() {
-                        if let Ok(retry_after_secs) = retry_after_value.parse::<u64>() {
-                            debug!("Retrying after {} seconds", retry_after_secs);
-                            sleep(Duration::from_secs(retry_after_secs)).await;
-                        } else {
-                            error!("Failed to parse Retry-After header value");
-                            return parse_error_response(response).await;
+                let wait = match response.headers().get(RETRY_AFTER) {
+                    Some(retry_after) => {
+                        match retry_after
+                            .to_str()
+                            .ok()
+                            .and_then(|value| value.parse::<u64>().ok())
+                        {
+                            Some(retry_after_secs) => {
+                                debug!("Retrying after {} seconds", retry_after_secs);
+                                Duration::from_secs(retry_after_secs)
+                            }
+                            None => {
+                                error!("Failed to parse Retry-After header value");
+                                return parse_error_response(response).await;
+                            }
                         }
-                    } else {
-                        error!("Failed to parse Retry-After header value");
-                        return parse_error_response(response).await;
                     }
-                } else {
-                    // Implement exponential backoff
-                    let backoff_secs = 2u64.pow(retries);
-                    debug!(
-                        "Retry-After header not found. Retrying after {} seconds",
-                        backoff_secs
-                    );
-                    sleep(Duration::from_secs(backoff_secs)).await;
+                    None => {
+                        // Implement exponential backoff
+                        let backoff_secs = 2u64.pow(retries);
+                        debug!(
+                            "Retry-After header not found. Retrying after {} seconds",
+                            backoff_secs
+                        );
+                        Duration::from_secs(backoff_secs)
+                    }
+                };
+                if let Some(budget) = retry_policy.max_total_retry_duration {
+                    let elapsed = now().duration_since(started).unwrap_or_default();
+                    if elapsed + wait > budget {
+                        error!("Retry budget of {:?} exhausted", budget);
+                        return Err(AcsError::Timeout(budget));
+                    }
                 }
+                delay(wait).await;
 
                 retries += 1;
+                if let Some(metrics) = &inner.metrics {
+                    metrics.record_retry();
+                }
 
                 // Retry the request
                 let new_response =
-                    send_request(method.clone(), url, request_id, body, acs_auth_method).await?;
+                    send_request(inner, method.clone(), url, request_id, body).await?;
                 response = new_response;
             }
+            StatusCode::UNAUTHORIZED
+                if !token_refreshed
+                    && !matches!(inner.auth_method, ACSAuthMethod::SharedKey(_)) =>
+            {
+                debug!("Unauthorized, retrying once with a new access token");
+                token_refreshed = true;
+                clear_access_token(inner).await;
+                if let Some(metrics) = &inner.metrics {
+                    metrics.record_retry();
+                }
+                response = send_request(inner, method.clone(), url, request_id, body).await?;
+            }
             _ => {
                 error!("Failed to send email: {:#?}", response);
                 return parse_error_response(response).await;
@@ -545,6 +2753,9 @@ where
 
 /// Parse the response from the email send operation.
 ///
+/// An empty body, e.g. from a `204 No Content`, is reported as `AcsError::EmptyResponse`
+/// rather than a parse error.
+///
 /// # Arguments
 ///
 /// * `response` - The `reqwest::Response` object.
@@ -556,10 +2767,66 @@ async fn parse_response<T>(response: reqwest::Response) -> EmailResult<T>
 where
     T: serde::de::DeserializeOwned,
 {
-    response
-        .json::<T>()
-        .await
-        .map_err(|e| to_error_response("Failed to parse response", e))
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response.text().await.map_err(to_transport_error)?;
+    if body.trim().is_empty() {
+        return Err(AcsError::EmptyResponse { status });
+    }
+    if let Some(content_type) = content_type.filter(|value| !value.contains("json")) {
+        return Err(to_error_response(
+            "Unexpected response content type",
+            format!("{} (body: {})", content_type, body_snippet(&body)),
+        ));
+    }
+    serde_json::from_str::<T>(&body).map_err(|e| {
+        to_error_response(
+            "Failed to parse response",
+            format!("{} (body: {})", e, body_snippet(&body)),
+        )
+    })
+}
+
+/// Shorten a response body for error messages, e.g. an HTML error page from a proxy.
+///
+/// # Arguments
+///
+/// * `body` - The response body.
+///
+/// # Returns
+///
+/// * `String` - The body, truncated to `MAX_BODY_SNIPPET_LEN` characters.
+fn body_snippet(body: &str) -> String {
+    let body = body.trim();
+    match body.char_indices().nth(MAX_BODY_SNIPPET_LEN) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
+    }
+}
+
+/// Parse a send or status response, converting an embedded error into an `AcsError::Api`.
+///
+/// # Arguments
+///
+/// * `response` - The `reqwest::Response` object.
+///
+/// # Returns
+///
+/// * `EmailResult<SentEmailResponse>` - The parsed response if it doesn't carry an error.
+async fn parse_sent_email_response(response: reqwest::Response) -> EmailResult<SentEmailResponse> {
+    let http_status = response.status().as_u16();
+    let email_response = parse_response::<SentEmailResponse>(response).await?;
+    match email_response.error {
+        Some(error) => Err(AcsError::api(
+            Some(http_status),
+            ErrorResponse { error: Some(error) },
+        )),
+        None => Ok(email_response),
+    }
 }
 
 /// Parse the error response from the email send operation.
@@ -572,24 +2839,387 @@ where
 ///
 /// * `EmailResult<String>` - The result of the error response parsing, containing the error response if successful.
 async fn parse_error_response(response: reqwest::Response) -> EmailResult<String> {
-    let error_response = parse_response::<ErrorResponse>(response).await?;
-    Err(error_response)
+    Err(to_api_error(response).await)
+}
+
+/// Convert an unsuccessful response into an `AcsError::Api` carrying its HTTP status.
+///
+/// A `429` becomes `AcsError::TooManyRequests`, with the rate limit hints of its headers.
+///
+/// Bodies that aren't an ACS error response, e.g. from a gateway, are kept as the error message.
+///
+/// # Arguments
+///
+/// * `response` - The `reqwest::Response` object.
+///
+/// # Returns
+///
+/// * `AcsError` - The API error with the HTTP status and error details.
+async fn to_api_error(response: reqwest::Response) -> AcsError {
+    let http_status = response.status();
+    let rate_limit = rate_limit_info(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    let error_response = serde_json::from_str::<ErrorResponse>(&body).unwrap_or_else(|_| {
+        let message = if body.trim().is_empty() {
+            http_status.to_string()
+        } else {
+            body_snippet(&body)
+        };
+        ErrorResponse {
+            error: Some(ErrorDetail {
+                message: Some(message),
+                ..Default::default()
+            }),
+        }
+    });
+    if http_status == StatusCode::TOO_MANY_REQUESTS {
+        return AcsError::TooManyRequests {
+            rate_limit: Box::new(rate_limit),
+            response: Box::new(error_response),
+        };
+    }
+    AcsError::api(Some(http_status.as_u16()), error_response)
+}
+
+/// Collect the rate limit hints of a response, logging any rate limit headers at debug level.
+///
+/// # Arguments
+///
+/// * `headers` - The response headers.
+///
+/// # Returns
+///
+/// * `RateLimitInfo` - The hints found, with the lowest value if several remaining quotas are sent.
+fn rate_limit_info(headers: &reqwest::header::HeaderMap) -> RateLimitInfo {
+    let mut rate_limit = RateLimitInfo::default();
+    for (name, value) in headers {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        let name = name.as_str();
+        if name.contains("ratelimit") || name.starts_with("retry-after") {
+            debug!("Rate limit header {}: {}", name, value);
+        }
+        if name.starts_with("x-ms-ratelimit-remaining") {
+            if let Ok(remaining) = value.trim().parse::<u64>() {
+                rate_limit.remaining =
+                    Some(rate_limit.remaining.map_or(remaining, |r| r.min(remaining)));
+            }
+        } else if name == "x-ms-ratelimit-reset" {
+            rate_limit.reset = Some(value.to_string());
+        } else if name == "retry-after-ms" {
+            if let Ok(millis) = value.trim().parse::<u64>() {
+                rate_limit.retry_after = Some(Duration::from_millis(millis));
+            }
+        } else if name == "retry-after" && rate_limit.retry_after.is_none() {
+            if let Ok(secs) = value.trim().parse::<u64>() {
+                rate_limit.retry_after = Some(Duration::from_secs(secs));
+            }
+        }
+    }
+    rate_limit
 }
 
 /// Create an error response for a missing status.
 ///
 /// # Returns
 ///
-/// * `ErrorResponse` - The error response indicating a missing status.
-fn create_missing_status_error() -> ErrorResponse {
+/// * `AcsError` - The error indicating a missing status.
+fn create_missing_status_error() -> AcsError {
     to_error_response("Missing status in response", "")
 }
 
+/// Read the status URL from the `Operation-Location` (or `Location`) header of a 202 response.
+///
+/// # Arguments
+///
+/// * `headers` - A reference to the response headers.
+///
+/// # Returns
+///
+/// * `Option<String>` - The status URL, `https://{host}/emails/operations/{id}?api-version=...`,
+///   if the header is present.
+fn operation_location(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get("operation-location")
+        .or_else(|| headers.get(reqwest::header::LOCATION))?
+        .to_str()
+        .ok()
+        .map(str::to_string)
+}
+
+/// Extract the operation ID from an operation status URL.
+///
+/// # Arguments
+///
+/// * `location` - The status URL, e.g. from an `Operation-Location` header.
+///
+/// # Returns
+///
+/// * `Option<String>` - The last path segment of the URL, if it is well-formed.
+fn operation_id_from_location(location: &str) -> Option<String> {
+    let url = Url::parse(location).ok()?;
+    let id = url
+        .path_segments()?
+        .rev()
+        .find(|segment| !segment.is_empty())?;
+    Some(id.to_string())
+}
+
+// Whether two URLs share scheme, host and port
+fn is_same_origin(url: &str, other: &str) -> bool {
+    match (Url::parse(url), Url::parse(other)) {
+        (Ok(url), Ok(other)) => url.origin() == other.origin(),
+        _ => false,
+    }
+}
+
+// Keep the status URL of an accepted send, dropping the oldest once the limit is reached
+fn remember_operation_location(locations: &OperationLocations, id: &str, location: String) {
+    let mut locations = locations.lock().unwrap_or_else(|e| e.into_inner());
+    if locations.len() >= MAX_OPERATION_LOCATIONS {
+        locations.pop_front();
+    }
+    locations.push_back((id.to_string(), location));
+}
+
+// The status URL kept for an operation ID, if its send returned one
+fn operation_location_for(locations: &OperationLocations, id: &str) -> Option<String> {
+    let locations = locations.lock().unwrap_or_else(|e| e.into_inner());
+    locations
+        .iter()
+        .rev()
+        .find(|(operation_id, _)| operation_id == id)
+        .map(|(_, location)| location.clone())
+}
+
+// Drop the status URL of an operation that reached a terminal status
+fn forget_operation_location(locations: &OperationLocations, id: &str) {
+    locations
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|(operation_id, _)| operation_id != id);
+}
+
 /// Create an error response for a missing ID.
 ///
 /// # Returns
 ///
-/// * `ErrorResponse` - The error response indicating a missing ID.
-fn create_missing_id_error() -> ErrorResponse {
+/// * `AcsError` - The error indicating a missing ID.
+fn create_missing_id_error() -> AcsError {
     to_error_response("Missing ID in response", "")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // A shared key client sending to the mock server over plain HTTP
+    fn mock_client(server: &MockServer) -> ACSClient {
+        ACSClientBuilder::with_connection_string(&format!(
+            "endpoint={}/;accesskey=c2VjcmV0",
+            server.uri()
+        ))
+        .host_suffix("127.0.0.1")
+        .build()
+        .unwrap()
+    }
+
+    async fn mount_status(server: &MockServer, status: &str) {
+        Mock::given(method("GET"))
+            .and(path("/emails/operations/op-1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": "op-1", "status": status })),
+            )
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn poll_until_terminal_polls_once_when_the_interval_exceeds_the_timeout() {
+        let server = MockServer::start().await;
+        mount_status(&server, "Succeeded").await;
+
+        let status = mock_client(&server)
+            .poll_until_terminal(
+                "op-1",
+                Duration::from_secs(60),
+                Duration::from_millis(50),
+                |_| async {},
+            )
+            .await
+            .unwrap();
+        assert_eq!(status, EmailSendStatusType::Succeeded);
+    }
+
+    #[tokio::test]
+    async fn poll_until_terminal_times_out_after_the_last_poll() {
+        let server = MockServer::start().await;
+        mount_status(&server, "Running").await;
+
+        let result = mock_client(&server)
+            .poll_until_terminal(
+                "op-1",
+                Duration::from_secs(60),
+                Duration::from_millis(50),
+                |_| async {},
+            )
+            .await;
+        assert!(matches!(result, Err(AcsError::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn create_headers_rejects_a_request_id_that_is_not_a_header_value() {
+        let url = Url::parse("https://xxx.communication.azure.com/emails:send").unwrap();
+        for client in [
+            ACSClientBuilder::with_connection_string(CONNECTION_STRING)
+                .build()
+                .unwrap(),
+            // Fails before acquiring a token, which would otherwise fail differently off Azure
+            ACSClientBuilder::with_managed_identity("xxx.communication.azure.com")
+                .build()
+                .unwrap(),
+        ] {
+            let result =
+                create_headers(&client.inner, &url, "POST", "id\r\nx-injected: 1", "{}").await;
+            match result {
+                Err(AcsError::Client(message)) => {
+                    assert!(message.starts_with("Invalid request ID"), "{}", message)
+                }
+                other => panic!("expected a client error, got {:?}", other),
+            }
+        }
+    }
+
+    const CONNECTION_STRING: &str =
+        "endpoint=https://xxx.communication.azure.com/;accesskey=c2VjcmV0";
+
+    // A credential that always returns the same token
+    #[derive(Debug)]
+    struct StaticCredential;
+
+    #[async_trait::async_trait]
+    impl TokenCredential for StaticCredential {
+        async fn get_token(&self, _scopes: &[&str]) -> azure_core::Result<AccessToken> {
+            Ok(AccessToken::new(
+                "token".to_string(),
+                time::OffsetDateTime::now_utc() + time::Duration::hours(1),
+            ))
+        }
+
+        async fn clear_cache(&self) -> azure_core::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn with_connection_string_uses_the_access_key_of_the_connection_string() {
+        let client = ACSClientBuilder::with_connection_string(CONNECTION_STRING)
+            .build()
+            .unwrap();
+        assert_eq!(client.inner.host, "xxx.communication.azure.com");
+        assert!(matches!(
+            client.inner.auth_method,
+            ACSAuthMethod::SharedKey(ref access_key) if access_key == "c2VjcmV0"
+        ));
+    }
+
+    #[test]
+    fn with_shared_key_uses_the_access_key() {
+        let client = ACSClientBuilder::with_shared_key("xxx.communication.azure.com", "c2VjcmV0")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.inner.auth_method,
+            ACSAuthMethod::SharedKey(ref access_key) if access_key == "c2VjcmV0"
+        ));
+    }
+
+    #[test]
+    fn with_service_principal_uses_the_client_secret_in_the_public_cloud() {
+        let client = ACSClientBuilder::with_service_principal(
+            "xxx.communication.azure.com",
+            "tenant",
+            "client",
+            "secret",
+        )
+        .build()
+        .unwrap();
+        assert!(matches!(
+            client.inner.auth_method,
+            ACSAuthMethod::ServicePrincipal {
+                ref tenant_id,
+                ref client_id,
+                ref client_secret,
+                cloud: Cloud::Public,
+            } if tenant_id == "tenant" && client_id == "client" && client_secret == "secret"
+        ));
+    }
+
+    #[test]
+    fn with_managed_identity_uses_the_default_token_timeout() {
+        let client = ACSClientBuilder::with_managed_identity("xxx.communication.azure.com")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.inner.auth_method,
+            ACSAuthMethod::ManagedIdentity {
+                cloud: Cloud::Public,
+                token_timeout: DEFAULT_MANAGED_IDENTITY_TIMEOUT,
+            }
+        ));
+    }
+
+    #[test]
+    fn with_token_credential_keeps_the_credential() {
+        let credential: Arc<dyn TokenCredential> = Arc::new(StaticCredential);
+        let client = ACSClientBuilder::with_token_credential(
+            "xxx.communication.azure.com",
+            credential.clone(),
+        )
+        .build()
+        .unwrap();
+        match &client.inner.auth_method {
+            ACSAuthMethod::TokenCredential {
+                credential: kept,
+                cloud: Cloud::Public,
+            } => assert!(Arc::ptr_eq(kept, &credential)),
+            _ => panic!("expected token credential authentication"),
+        }
+    }
+
+    #[test]
+    // The deprecated fluent methods must still reject conflicting settings
+    #[allow(deprecated)]
+    fn build_rejects_a_connection_string_with_an_auth_method() {
+        let result = ACSClientBuilder::new()
+            .connection_string(CONNECTION_STRING)
+            .managed_identity()
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn build_rejects_a_connection_string_with_a_different_host() {
+        let result = ACSClientBuilder::new()
+            .connection_string(CONNECTION_STRING)
+            .host("yyy.communication.azure.com")
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn build_accepts_a_connection_string_with_its_own_host() {
+        let result = ACSClientBuilder::new()
+            .connection_string(CONNECTION_STRING)
+            .host("https://XXX.communication.azure.com/")
+            .build();
+        assert!(result.is_ok());
+    }
+}
@@ -1,53 +1,8 @@
+use crate::adapters::gateways::signing::get_host_authority;
 use crate::domain::entities::models::EndPointParams;
-use base64::{engine::general_purpose, Engine as _};
-use hmac::{Hmac, Mac};
-use httpdate::fmt_http_date;
 use log::debug;
-use reqwest::header::HeaderMap;
-use sha2::{Digest, Sha256};
-use std::time::SystemTime;
 use url::Url;
 
-type HmacSha256 = Hmac<Sha256>;
-
-/// Computes the SHA-256 hash of the given content and encodes it in base64.
-///
-/// # Arguments
-///
-/// * `content` - A string slice that holds the content to be hashed.
-///
-/// # Returns
-///
-/// * `String` - The base64 encoded SHA-256 hash of the content.
-pub fn compute_content_sha256(content: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    let result = hasher.finalize();
-    general_purpose::STANDARD.encode(&result)
-}
-
-/// Computes the HMAC-SHA256 signature for the given string using the provided secret.
-///
-/// # Arguments
-///
-/// * `string_to_sign` - A string slice that holds the string to be signed.
-/// * `secret` - A string slice that holds the secret key.
-///
-/// # Returns
-///
-/// * `Result<String, String>` - The base64 encoded HMAC-SHA256 signature or an error message.
-pub fn compute_signature(string_to_sign: &str, secret: &str) -> Result<String, String> {
-    let decoded_secret = general_purpose::STANDARD
-        .decode(secret)
-        .map_err(|e| format!("Failed to decode secret: {}", e))?;
-    let mut mac = HmacSha256::new_from_slice(&decoded_secret)
-        .map_err(|e| format!("Failed to create HMAC instance: {}", e))?;
-    mac.update(string_to_sign.as_bytes());
-    let result = mac.finalize();
-    let code_bytes = result.into_bytes();
-    Ok(general_purpose::STANDARD.encode(code_bytes))
-}
-
 /// Parses the endpoint string and extracts the host name and access key.
 ///
 /// # Arguments
@@ -73,14 +28,15 @@ pub fn parse_endpoint(endpoint: &str) -> Result<EndPointParams, String> {
         if let Some(host) = param.strip_prefix("endpoint=") {
             let parsed_url =
                 Url::parse(host).map_err(|e| format!("Invalid endpoint URL: {}", e))?;
-            end_point_params.host_name = parsed_url
-                .host_str()
-                .ok_or_else(|| "Missing host in endpoint URL".to_string())?
-                .to_string();
+            let host_authority = get_host_authority(&parsed_url)?;
+            // Keep the scheme of a plain HTTP endpoint, which the client only accepts for loopback hosts
+            end_point_params.host_name = match parsed_url.scheme() {
+                "http" => format!("http://{}", host_authority),
+                _ => host_authority,
+            };
             debug!("Host name: {}", end_point_params.host_name);
         } else if let Some(key) = param.strip_prefix("accesskey=") {
             end_point_params.access_key = key.to_string();
-            debug!("Access key: {}", end_point_params.access_key);
         } else {
             return Err("Invalid parameter in connection string".to_string());
         }
@@ -89,56 +45,74 @@ pub fn parse_endpoint(endpoint: &str) -> Result<EndPointParams, String> {
     Ok(end_point_params)
 }
 
-/// Creates the request headers for the given parameters.
+/// Checks that a host name belongs to the expected ACS domain.
+///
+/// The host matches when it ends with `suffix` on a label boundary, ignoring case and any port,
+/// so `xxx.communication.azure.com:443` matches `.communication.azure.com` but
+/// `evilcommunication.azure.com` does not.
 ///
 /// # Arguments
 ///
-/// * `url_endpoint` - A reference to the `Url` struct representing the endpoint URL.
-/// * `http_method` - A string slice that holds the HTTP method.
-/// * `request_id` - A string slice that holds the request ID.
-/// * `json_payload` - A string slice that holds the JSON payload.
-/// * `access_key` - A string slice that holds the access key.
+/// * `host_name` - A string slice that holds the host name, optionally with a port.
+/// * `suffix` - A string slice that holds the expected domain suffix.
 ///
 /// # Returns
 ///
-/// * `Result<HeaderMap, String>` - The created request headers or an error message.
-pub fn get_request_header(
-    url_endpoint: &Url,
-    http_method: &str,
-    request_id: &str,
-    json_payload: &str,
-    access_key: &str,
-) -> Result<HeaderMap, String> {
-    let mut headers = HeaderMap::new();
-    let content_hash = compute_content_sha256(json_payload);
-    let now = SystemTime::now();
-    let http_date = fmt_http_date(now);
+/// * `Result<(), String>` - An error message if the host doesn't match the suffix.
+pub fn validate_host_suffix(host_name: &str, suffix: &str) -> Result<(), String> {
+    let host = host_name
+        .rsplit_once(':')
+        .map_or(host_name, |(host, _)| host)
+        .to_ascii_lowercase();
+    let domain = suffix.trim_start_matches('.').to_ascii_lowercase();
+    let is_match = host
+        .strip_suffix(&domain)
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with('.'));
+    if !is_match {
+        return Err(format!(
+            "Endpoint host '{}' is not an ACS endpoint ending with '{}'",
+            host_name, suffix
+        ));
+    }
+    Ok(())
+}
 
-    headers.insert("Content-Type", "application/json".parse().unwrap());
-    headers.insert("repeatability-request-id", request_id.parse().unwrap());
-    headers.insert("repeatability-first-sent", http_date.parse().unwrap());
-    headers.insert("x-ms-date", http_date.parse().unwrap());
-    headers.insert("x-ms-content-sha256", content_hash.parse().unwrap());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let host_authority = url_endpoint
-        .host_str()
-        .ok_or_else(|| "Missing host in URL".to_string())?;
-    let path_and_query = match url_endpoint.query() {
-        Some(query) => format!("{}?{}", url_endpoint.path(), query),
-        None => url_endpoint.path().to_string(),
-    };
-    let string_to_sign = format!(
-        "{}\n{}\n{};{};{}",
-        http_method, path_and_query, http_date, host_authority, content_hash
-    );
-    debug!("String to sign:\n{}", string_to_sign);
+    #[test]
+    fn validate_host_suffix_accepts_an_acs_host_with_a_port() {
+        assert!(validate_host_suffix(
+            "xxx.communication.azure.com:443",
+            ".communication.azure.com"
+        )
+        .is_ok());
+        assert!(
+            validate_host_suffix("XXX.Communication.Azure.com", ".communication.azure.com").is_ok()
+        );
+    }
 
-    let signature = compute_signature(&string_to_sign, access_key)?;
-    let authorization = format!(
-        "HMAC-SHA256 SignedHeaders=x-ms-date;host;x-ms-content-sha256&Signature={}",
-        signature
-    );
-    headers.insert("Authorization", authorization.parse().unwrap());
+    #[test]
+    fn validate_host_suffix_accepts_a_sovereign_cloud_suffix() {
+        assert!(
+            validate_host_suffix("xxx.communication.azure.us", ".communication.azure.us").is_ok()
+        );
+        assert!(
+            validate_host_suffix("xxx.communication.azure.us", ".communication.azure.com").is_err()
+        );
+    }
 
-    Ok(headers)
+    #[test]
+    fn validate_host_suffix_rejects_a_host_outside_the_domain() {
+        assert!(
+            validate_host_suffix("evilcommunication.azure.com", ".communication.azure.com")
+                .is_err()
+        );
+        assert!(validate_host_suffix(
+            "communication.azure.com.evil.example",
+            ".communication.azure.com"
+        )
+        .is_err());
+    }
 }
@@ -0,0 +1,203 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+use crate::adapters::gateways::acs_shared_key::parse_endpoint;
+use std::env;
+
+/// A validated ACS client configuration, consumed by `ACSClientBuilder::from_config`.
+///
+/// The library only reads real environment variables and never loads a `.env` file itself, so
+/// loading one stays up to the application. `dotenv` does not override variables that are
+/// already set, so real environment variables take precedence over a `.env` file.
+#[derive(Clone)]
+pub struct Config {
+    pub(crate) credential: ConfigCredential,
+}
+
+/// The endpoint and credentials held by a `Config`.
+#[derive(Clone)]
+pub(crate) enum ConfigCredential {
+    ConnectionString(String),
+    ServicePrincipal {
+        endpoint: String,
+        tenant_id: String,
+        client_id: String,
+        client_secret: String,
+    },
+    ManagedIdentity {
+        endpoint: String,
+    },
+}
+
+impl Config {
+    /// Loads the configuration from environment variables, picking the authentication method.
+    ///
+    /// The variables are checked in this order:
+    ///
+    /// * `ACS_CONNECTION_STRING` - shared key authentication via a connection string.
+    /// * `ACS_ENDPOINT` with `AZURE_TENANT_ID`, `AZURE_CLIENT_ID` and `AZURE_CLIENT_SECRET` -
+    ///   service principal authentication.
+    /// * `ACS_ENDPOINT` alone - managed identity authentication.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config, String>` - The configuration, or an error if none is found or it is invalid.
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|name| env::var(name).ok())
+    }
+
+    // Picks the configuration from variables returned by `lookup`, treating empty values as unset
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let var = |name: &str| lookup(name).filter(|value| !value.is_empty());
+
+        if let Some(connection_string) = var("ACS_CONNECTION_STRING") {
+            return Self::from_connection_string(&connection_string);
+        }
+
+        let endpoint = var("ACS_ENDPOINT").ok_or_else(|| {
+            "Either ACS_CONNECTION_STRING or ACS_ENDPOINT must be set".to_string()
+        })?;
+        let service_principal = (
+            var("AZURE_TENANT_ID"),
+            var("AZURE_CLIENT_ID"),
+            var("AZURE_CLIENT_SECRET"),
+        );
+        let credential = match service_principal {
+            (Some(tenant_id), Some(client_id), Some(client_secret)) => {
+                ConfigCredential::ServicePrincipal {
+                    endpoint,
+                    tenant_id,
+                    client_id,
+                    client_secret,
+                }
+            }
+            (None, None, None) => ConfigCredential::ManagedIdentity { endpoint },
+            _ => {
+                return Err(
+                    "AZURE_TENANT_ID, AZURE_CLIENT_ID and AZURE_CLIENT_SECRET must be set together"
+                        .to_string(),
+                );
+            }
+        };
+        Ok(Config { credential })
+    }
+
+    /// Creates a shared key configuration from a connection string.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection_string` - A string slice that holds the `endpoint=...;accesskey=...` connection string.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config, String>` - The configuration, or an error if the connection string is malformed.
+    pub fn from_connection_string(connection_string: &str) -> Result<Self, String> {
+        parse_endpoint(connection_string)
+            .map_err(|e| format!("Failed to parse connection string: {}", e))?;
+        Ok(Config {
+            credential: ConfigCredential::ConnectionString(connection_string.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const CONNECTION_STRING: &str =
+        "endpoint=https://contoso.communication.azure.com/;accesskey=c2VjcmV0";
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<Config, String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Config::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn from_lookup_prefers_the_connection_string_over_the_endpoint() {
+        let config = from_vars(&[
+            ("ACS_CONNECTION_STRING", CONNECTION_STRING),
+            ("ACS_ENDPOINT", "https://other.communication.azure.com/"),
+            ("AZURE_TENANT_ID", "tenant"),
+        ])
+        .unwrap();
+        assert!(matches!(
+            config.credential,
+            ConfigCredential::ConnectionString(ref value) if value == CONNECTION_STRING
+        ));
+    }
+
+    #[test]
+    fn from_lookup_rejects_a_malformed_connection_string() {
+        let error = from_vars(&[("ACS_CONNECTION_STRING", "not a connection string")])
+            .err()
+            .unwrap();
+        assert!(
+            error.starts_with("Failed to parse connection string"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn from_lookup_uses_a_service_principal_when_all_three_variables_are_set() {
+        let config = from_vars(&[
+            ("ACS_ENDPOINT", "https://contoso.communication.azure.com/"),
+            ("AZURE_TENANT_ID", "tenant"),
+            ("AZURE_CLIENT_ID", "client"),
+            ("AZURE_CLIENT_SECRET", "secret"),
+        ])
+        .unwrap();
+        assert!(matches!(
+            config.credential,
+            ConfigCredential::ServicePrincipal { ref endpoint, ref tenant_id, ref client_id, ref client_secret }
+                if endpoint == "https://contoso.communication.azure.com/"
+                    && tenant_id == "tenant"
+                    && client_id == "client"
+                    && client_secret == "secret"
+        ));
+    }
+
+    #[test]
+    fn from_lookup_falls_back_to_managed_identity_with_the_endpoint_alone() {
+        let config = from_vars(&[
+            ("ACS_CONNECTION_STRING", ""),
+            ("ACS_ENDPOINT", "https://contoso.communication.azure.com/"),
+            ("AZURE_CLIENT_SECRET", ""),
+        ])
+        .unwrap();
+        assert!(matches!(
+            config.credential,
+            ConfigCredential::ManagedIdentity { ref endpoint }
+                if endpoint == "https://contoso.communication.azure.com/"
+        ));
+    }
+
+    #[test]
+    fn from_lookup_requires_the_service_principal_variables_together() {
+        let error = from_vars(&[
+            ("ACS_ENDPOINT", "https://contoso.communication.azure.com/"),
+            ("AZURE_TENANT_ID", "tenant"),
+            ("AZURE_CLIENT_ID", "client"),
+        ])
+        .err()
+        .unwrap();
+        assert_eq!(
+            error,
+            "AZURE_TENANT_ID, AZURE_CLIENT_ID and AZURE_CLIENT_SECRET must be set together"
+        );
+    }
+
+    #[test]
+    fn from_lookup_requires_a_connection_string_or_an_endpoint() {
+        let error = from_vars(&[("AZURE_TENANT_ID", "tenant")]).err().unwrap();
+        assert_eq!(
+            error,
+            "Either ACS_CONNECTION_STRING or ACS_ENDPOINT must be set"
+        );
+    }
+}
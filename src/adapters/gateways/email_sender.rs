@@ -0,0 +1,65 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+use crate::adapters::gateways::acs_email::{ACSClient, EmailResult};
+use crate::domain::entities::models::{EmailSendStatusType, SentEmail};
+use std::future::Future;
+use std::pin::Pin;
+
+/// A boxed `Send` future, returned by `EmailSender` so the trait can be used as `dyn EmailSender`.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Sends emails and queries their status.
+///
+/// Application code can depend on this trait instead of `ACSClient` directly, so it can be
+/// exercised with the `FakeSender` from the `testing` feature without hitting Azure. The methods
+/// return boxed futures, so an `Arc<dyn EmailSender>` can be shared as web framework state.
+pub trait EmailSender: Send + Sync {
+    /// Send an email.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - A reference to the `SentEmail` struct containing the email details.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<String>` - The operation ID of the sent email if successful.
+    fn send_email<'a>(&'a self, email: &'a SentEmail) -> BoxFuture<'a, EmailResult<String>>;
+
+    /// Get the status of a sent email.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The operation ID returned by `send_email`.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailResult<EmailSendStatusType>` - The current status of the email if successful.
+    fn get_email_status<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, EmailResult<EmailSendStatusType>>;
+}
+
+// reqwest futures are not `Send` on wasm32
+#[cfg(not(target_arch = "wasm32"))]
+impl EmailSender for ACSClient {
+    fn send_email<'a>(&'a self, email: &'a SentEmail) -> BoxFuture<'a, EmailResult<String>> {
+        Box::pin(ACSClient::send_email(self, email))
+    }
+
+    fn get_email_status<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, EmailResult<EmailSendStatusType>> {
+        Box::pin(ACSClient::get_email_status(self, id))
+    }
+}
+
+// Fails to compile if `ACSClient` stops being shareable across threads and tasks
+#[cfg(not(target_arch = "wasm32"))]
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<ACSClient>();
+};
@@ -0,0 +1,176 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+use crate::adapters::gateways::acs_email::EmailResult;
+use crate::adapters::gateways::email_sender::{BoxFuture, EmailSender};
+use crate::domain::entities::models::{AcsError, EmailSendStatusType, SentEmail};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// An in-memory `EmailSender` for unit-testing code that sends emails.
+///
+/// Sent emails are recorded instead of being delivered, and `get_email_status` returns the
+/// statuses queued with `push_status`, falling back to a default status once the queue is empty.
+pub struct FakeSender {
+    sent: Mutex<Vec<SentEmail>>,
+    statuses: Mutex<VecDeque<EmailSendStatusType>>,
+    default_status: EmailSendStatusType,
+}
+
+impl Default for FakeSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeSender {
+    /// Creates a new `FakeSender` whose emails report `Succeeded`.
+    ///
+    /// # Returns
+    ///
+    /// * `FakeSender` - A new instance with no sent emails.
+    pub fn new() -> Self {
+        FakeSender {
+            sent: Mutex::new(Vec::new()),
+            statuses: Mutex::new(VecDeque::new()),
+            default_status: EmailSendStatusType::Succeeded,
+        }
+    }
+
+    /// Sets the status returned once the queued statuses are used up.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to return by default.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The `FakeSender` instance.
+    pub fn default_status(mut self, status: EmailSendStatusType) -> Self {
+        self.default_status = status;
+        self
+    }
+
+    /// Queues a status to be returned by the next `get_email_status` call.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to return.
+    pub fn push_status(&self, status: EmailSendStatusType) {
+        self.statuses.lock().unwrap().push_back(status);
+    }
+
+    /// Returns the emails sent so far, in order.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<SentEmail>` - A copy of the recorded emails.
+    pub fn sent_emails(&self) -> Vec<SentEmail> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl EmailSender for FakeSender {
+    fn send_email<'a>(&'a self, email: &'a SentEmail) -> BoxFuture<'a, EmailResult<String>> {
+        let mut sent = self.sent.lock().unwrap();
+        sent.push(email.clone());
+        let id = format!("fake-operation-{}", sent.len());
+        Box::pin(async move { Ok(id) })
+    }
+
+    fn get_email_status<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, EmailResult<EmailSendStatusType>> {
+        let count = self.sent.lock().unwrap().len();
+        let known = id
+            .strip_prefix("fake-operation-")
+            .and_then(|index| index.parse::<usize>().ok())
+            .is_some_and(|index| (1..=count).contains(&index));
+        let result = if known {
+            let status = self.statuses.lock().unwrap().pop_front();
+            Ok(status.unwrap_or(self.default_status))
+        } else {
+            Err(AcsError::Client(format!("Unknown operation ID: {}", id)))
+        };
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::models::SentEmailBuilder;
+
+    fn email(subject: &str) -> SentEmail {
+        SentEmailBuilder::new()
+            .sender("donotreply@example.com".to_string())
+            .subject(subject)
+            .plain_text("Body")
+            .to("jane@example.com")
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sent_emails_are_recorded_in_order() {
+        let sender = FakeSender::new();
+        assert_eq!(
+            sender.send_email(&email("First")).await.unwrap(),
+            "fake-operation-1"
+        );
+        assert_eq!(
+            sender.send_email(&email("Second")).await.unwrap(),
+            "fake-operation-2"
+        );
+        let subjects: Vec<Option<String>> = sender
+            .sent_emails()
+            .into_iter()
+            .map(|email| email.content.subject)
+            .collect();
+        assert_eq!(
+            subjects,
+            [Some("First".to_string()), Some("Second".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn queued_statuses_are_returned_before_the_default_status() {
+        let sender = FakeSender::new().default_status(EmailSendStatusType::Failed);
+        let id = sender.send_email(&email("Subject")).await.unwrap();
+        sender.push_status(EmailSendStatusType::NotStarted);
+        sender.push_status(EmailSendStatusType::Running);
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            statuses.push(sender.get_email_status(&id).await.unwrap());
+        }
+        assert_eq!(
+            statuses,
+            [
+                EmailSendStatusType::NotStarted,
+                EmailSendStatusType::Running,
+                EmailSendStatusType::Failed,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn an_unknown_operation_id_is_a_client_error() {
+        let sender = FakeSender::new();
+        sender.send_email(&email("Subject")).await.unwrap();
+        for id in [
+            "op-1",
+            "fake-operation-x",
+            "fake-operation-0",
+            "fake-operation-2",
+        ] {
+            assert!(
+                matches!(sender.get_email_status(id).await, Err(AcsError::Client(_))),
+                "{}",
+                id
+            );
+        }
+    }
+}
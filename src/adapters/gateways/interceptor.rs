@@ -0,0 +1,97 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::{Request, Response};
+
+/// Observes every request sent to ACS and every response received, e.g. for audit logs.
+///
+/// Covers send and status requests, including each retry, but not token or sender domain
+/// lookups. Requests are already signed, so an interceptor that logs headers should log
+/// `masked_headers` instead, which hides the `Authorization` header.
+///
+/// Both methods default to doing nothing, so implementations only override what they observe.
+///
+/// # Example
+///
+/// ```
+/// use azure_email_service::adapters::gateways::interceptor::{masked_headers, Interceptor};
+/// use std::sync::Mutex;
+///
+/// #[derive(Default)]
+/// struct AuditLog {
+///     calls: Mutex<Vec<String>>,
+/// }
+///
+/// impl Interceptor for AuditLog {
+///     fn before_send(&self, request: &reqwest::Request) {
+///         let headers = masked_headers(request.headers());
+///         self.calls.lock().unwrap().push(format!(
+///             "{} {} (authorization: {:?})",
+///             request.method(),
+///             request.url(),
+///             headers["authorization"]
+///         ));
+///     }
+///
+///     fn after_receive(&self, response: &reqwest::Response) {
+///         let call = format!("{} {}", response.status(), response.url());
+///         self.calls.lock().unwrap().push(call);
+///     }
+/// }
+///
+/// let log = AuditLog::default();
+/// let mut request = reqwest::Request::new(
+///     reqwest::Method::GET,
+///     "https://example.communication.azure.com/emails/operations/1"
+///         .parse()
+///         .unwrap(),
+/// );
+/// request
+///     .headers_mut()
+///     .insert("authorization", "Bearer secret".parse().unwrap());
+/// log.before_send(&request);
+/// assert_eq!(
+///     log.calls.lock().unwrap()[0],
+///     "GET https://example.communication.azure.com/emails/operations/1 (authorization: \"***\")"
+/// );
+/// ```
+pub trait Interceptor: Send + Sync {
+    /// Called right before a request is sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The signed request.
+    fn before_send(&self, request: &Request) {
+        let _ = request;
+    }
+
+    /// Called when response headers are received, before the body is read.
+    ///
+    /// Not called when no response arrives, e.g. on a connection error.
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - The response, with its status, headers and URL.
+    fn after_receive(&self, response: &Response) {
+        let _ = response;
+    }
+}
+
+/// Returns a copy of request headers that is safe to log, with `Authorization` masked.
+///
+/// # Arguments
+///
+/// * `headers` - The headers of a request.
+///
+/// # Returns
+///
+/// * `HeaderMap` - The headers, with the value of `Authorization` replaced by `***`.
+pub fn masked_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut masked = headers.clone();
+    if let Some(value) = masked.get_mut(AUTHORIZATION) {
+        *value = HeaderValue::from_static("***");
+    }
+    masked
+}
@@ -0,0 +1,58 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+#[cfg(feature = "testing")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Receives metrics from `ACSClient`, to forward them to any metrics library.
+///
+/// Both methods default to doing nothing, so implementations only override what they record.
+/// Clients without a sink skip the calls entirely.
+pub trait MetricsSink: Send + Sync {
+    /// Called once per sent email, after any retries.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The HTTP status of the final response, `0` if no response was received.
+    /// * `latency` - The total time taken, including retries.
+    fn record_send(&self, status: u16, latency: Duration) {
+        let _ = (status, latency);
+    }
+
+    /// Called before each retry of a send request.
+    fn record_retry(&self) {}
+}
+
+/// A `MetricsSink` that counts invocations, for tests.
+#[cfg(feature = "testing")]
+#[derive(Debug, Default)]
+pub struct CountingMetricsSink {
+    sends: AtomicUsize,
+    retries: AtomicUsize,
+}
+
+#[cfg(feature = "testing")]
+impl CountingMetricsSink {
+    /// Returns the number of `record_send` calls.
+    pub fn sends(&self) -> usize {
+        self.sends.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of `record_retry` calls.
+    pub fn retries(&self) -> usize {
+        self.retries.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl MetricsSink for CountingMetricsSink {
+    fn record_send(&self, _status: u16, _latency: Duration) {
+        self.sends.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::SeqCst);
+    }
+}
@@ -1,2 +1,11 @@
 pub mod acs_email;
 mod acs_shared_key;
+pub mod config;
+pub mod email_sender;
+#[cfg(feature = "testing")]
+pub mod fake_sender;
+pub mod interceptor;
+pub mod metrics;
+mod runtime;
+pub mod sent_log;
+pub mod signing;
@@ -0,0 +1,42 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+//! Timer and clock helpers that work on both native targets and `wasm32-unknown-unknown`.
+//!
+//! Check the wasm build with `cargo check --target wasm32-unknown-unknown --no-default-features`.
+
+use std::time::{Duration, SystemTime};
+
+/// Wait for the given duration.
+///
+/// Uses `tokio::time::sleep` on native targets and `gloo-timers` on wasm32.
+///
+/// # Arguments
+///
+/// * `duration` - The time to wait.
+pub(crate) async fn delay(duration: Duration) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+}
+
+/// Get the current time.
+///
+/// `SystemTime::now()` panics on `wasm32-unknown-unknown`, so the time is read from the
+/// JavaScript `Date` there instead.
+///
+/// # Returns
+///
+/// * `SystemTime` - The current time.
+pub(crate) fn now() -> SystemTime {
+    #[cfg(target_arch = "wasm32")]
+    {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(js_sys::Date::now() as u64)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        SystemTime::now()
+    }
+}
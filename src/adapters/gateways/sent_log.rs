@@ -0,0 +1,78 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// An email accepted by ACS, as recorded in a `SentLog`.
+///
+/// Addresses are deliberately not included, so records can be stored without handling PII.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentRecord {
+    /// The operation ID returned by ACS, used to query the status later.
+    pub operation_id: String,
+
+    /// The repeatability request ID the email was sent with.
+    pub request_id: String,
+
+    /// When ACS accepted the email.
+    pub sent_at: SystemTime,
+
+    /// The number of to, cc and bcc recipients.
+    pub recipient_count: usize,
+}
+
+/// Receives the operation IDs of accepted emails, e.g. to reconcile sends later.
+///
+/// The ACS email API can't list past operations, so recording them when they are returned
+/// is the only way to enumerate what a client has sent.
+pub trait SentLog: Send + Sync {
+    /// Called once for every email ACS accepts.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The operation and request IDs of the accepted email.
+    fn record(&self, record: SentRecord);
+}
+
+/// A `SentLog` that keeps records in memory.
+#[derive(Debug, Default)]
+pub struct InMemorySentLog {
+    records: Mutex<Vec<SentRecord>>,
+}
+
+impl InMemorySentLog {
+    /// Creates an empty log.
+    ///
+    /// # Returns
+    ///
+    /// * `InMemorySentLog` - A log without records.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the records logged so far, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<SentRecord>` - A copy of the records.
+    pub fn records(&self) -> Vec<SentRecord> {
+        self.records.lock().unwrap().clone()
+    }
+
+    /// Removes and returns the records logged so far, e.g. to flush them to durable storage.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<SentRecord>` - The records, oldest first.
+    pub fn drain(&self) -> Vec<SentRecord> {
+        std::mem::take(&mut *self.records.lock().unwrap())
+    }
+}
+
+impl SentLog for InMemorySentLog {
+    fn record(&self, record: SentRecord) {
+        self.records.lock().unwrap().push(record);
+    }
+}
@@ -1,12 +1,63 @@
+use base64::{engine::general_purpose, Engine as _};
+use log::warn;
+use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fmt::Formatter;
+use std::fs;
+use std::io::Read;
+use std::iter::Flatten;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::SystemTime;
+use uuid::Uuid;
+
+/// Standard headers ACS accepts in addition to custom `x-` prefixed headers.
+pub const ALLOWED_STANDARD_HEADERS: [&str; 3] =
+    ["List-Unsubscribe", "List-Unsubscribe-Post", "Importance"];
+
+/// The default maximum total size of all attachments in bytes, matching the ACS 10 MB message cap.
+pub const DEFAULT_MAX_ATTACHMENTS_SIZE: usize = 10 * 1024 * 1024;
+
+/// The default HTML body size in bytes above which `SentEmailBuilder::build` warns, since some
+/// mail clients clip larger messages.
+pub const DEFAULT_MAX_HTML_SIZE: usize = 100 * 1024;
+
+/// The default maximum base64 encoded size of a file attachment, the encoded size of
+/// `DEFAULT_MAX_ATTACHMENTS_SIZE` bytes.
+pub const DEFAULT_MAX_ENCODED_ATTACHMENT_SIZE: usize = DEFAULT_MAX_ATTACHMENTS_SIZE.div_ceil(3) * 4;
 
 /// Represents the status of an email send operation.
-#[derive(Serialize, Deserialize, Debug)]
+///
+/// Deserializes from a bare string (`"Running"`) as well as the object form
+/// (`{ "status": "Running" }`) some API versions return in the operation resource.
+#[derive(Serialize, Debug, Clone, Copy)]
 pub struct EmailSendStatus(EmailSendStatusType);
 
+impl<'de> Deserialize<'de> for EmailSendStatus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawStatus {
+            Bare(String),
+            Object { status: String },
+        }
+
+        let status = match RawStatus::deserialize(deserializer)? {
+            RawStatus::Bare(status) | RawStatus::Object { status } => status,
+        };
+        // `FromStr` maps statuses this crate doesn't know yet to `Unknown`
+        Ok(EmailSendStatus(
+            status.parse().unwrap_or(EmailSendStatusType::Unknown),
+        ))
+    }
+}
+
 impl EmailSendStatus {
     /// Converts the `EmailSendStatus` to its underlying type.
     ///
@@ -19,7 +70,11 @@ impl EmailSendStatus {
 }
 
 /// Enum representing the possible statuses of an email send operation.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+///
+/// Marked `#[non_exhaustive]` because ACS may introduce new operation statuses.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+#[non_exhaustive]
 pub enum EmailSendStatusType {
     Unknown,
     Canceled,
@@ -29,6 +84,102 @@ pub enum EmailSendStatusType {
     Succeeded,
 }
 
+impl EmailSendStatusType {
+    /// Returns whether the operation has finished, so polling its status can stop.
+    ///
+    /// `Unknown` counts as terminal, since polling can't make progress on a status it doesn't
+    /// understand.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` for `Unknown`, `Canceled`, `Failed` and `Succeeded`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::EmailSendStatusType;
+    ///
+    /// assert!(EmailSendStatusType::Unknown.is_terminal());
+    /// assert!(EmailSendStatusType::Canceled.is_terminal());
+    /// assert!(EmailSendStatusType::Failed.is_terminal());
+    /// assert!(!EmailSendStatusType::NotStarted.is_terminal());
+    /// assert!(!EmailSendStatusType::Running.is_terminal());
+    /// assert!(EmailSendStatusType::Succeeded.is_terminal());
+    /// ```
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            EmailSendStatusType::Unknown
+                | EmailSendStatusType::Canceled
+                | EmailSendStatusType::Failed
+                | EmailSendStatusType::Succeeded
+        )
+    }
+
+    /// Returns whether the email was delivered to ACS successfully.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` only for `Succeeded`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::EmailSendStatusType;
+    ///
+    /// assert!(!EmailSendStatusType::Unknown.is_success());
+    /// assert!(!EmailSendStatusType::Canceled.is_success());
+    /// assert!(!EmailSendStatusType::Failed.is_success());
+    /// assert!(!EmailSendStatusType::NotStarted.is_success());
+    /// assert!(!EmailSendStatusType::Running.is_success());
+    /// assert!(EmailSendStatusType::Succeeded.is_success());
+    /// ```
+    pub fn is_success(&self) -> bool {
+        matches!(self, EmailSendStatusType::Succeeded)
+    }
+
+    /// Classifies the status as a result, e.g. for
+    /// `client.send_and_wait(...).await.and_then(|(_, status)| status.into_result())`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), AcsError>` - `Ok` for `Succeeded`, `AcsError::NotTerminal` for `NotStarted`
+    ///   and `Running`, and `AcsError::Unsuccessful` for any other status.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::{AcsError, EmailSendStatusType};
+    ///
+    /// assert!(EmailSendStatusType::Succeeded.into_result().is_ok());
+    /// for status in [
+    ///     EmailSendStatusType::Failed,
+    ///     EmailSendStatusType::Canceled,
+    ///     EmailSendStatusType::Unknown,
+    /// ] {
+    ///     assert!(matches!(status.into_result(), Err(AcsError::Unsuccessful(s)) if s == status));
+    /// }
+    /// for status in [EmailSendStatusType::NotStarted, EmailSendStatusType::Running] {
+    ///     assert!(matches!(status.into_result(), Err(AcsError::NotTerminal(s)) if s == status));
+    /// }
+    /// ```
+    pub fn into_result(self) -> Result<(), AcsError> {
+        match self {
+            EmailSendStatusType::Succeeded => Ok(()),
+            EmailSendStatusType::NotStarted | EmailSendStatusType::Running => {
+                Err(AcsError::NotTerminal(self))
+            }
+            _ => Err(AcsError::Unsuccessful(self)),
+        }
+    }
+}
+
+impl From<EmailSendStatusType> for Result<(), AcsError> {
+    fn from(status: EmailSendStatusType) -> Self {
+        status.into_result()
+    }
+}
+
 /// Represents the response received after sending an email.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SentEmailResponse {
@@ -56,8 +207,10 @@ pub struct ErrorDetail {
     #[serde(rename = "code")]
     pub code: Option<String>,
 
-    // #[serde(rename = "details")]
-    // pub(crate) details: Option<ErrorDetail>,
+    /// The nested error details, e.g. one entry per invalid recipient.
+    #[serde(rename = "details")]
+    pub details: Option<Vec<ErrorDetail>>,
+
     /// The error message.
     #[serde(rename = "message")]
     pub message: Option<String>,
@@ -70,9 +223,9 @@ pub struct ErrorDetail {
 /// Represents additional information about an error.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ErrorAdditionalInfo {
-    /// The additional information.
+    /// The additional information, an object whose shape depends on `info_type`.
     #[serde(rename = "info")]
-    pub info: Option<String>,
+    pub info: Option<serde_json::Value>,
 
     /// The type of the additional information.
     #[serde(rename = "type")]
@@ -80,7 +233,7 @@ pub struct ErrorAdditionalInfo {
 }
 
 /// Represents an email to be sent.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SentEmail {
     /// The headers of the email.
     #[serde(rename = "headers", skip_serializing_if = "Option::is_none")]
@@ -103,10 +256,14 @@ pub struct SentEmail {
     pub attachments: Option<Vec<EmailAttachment>>,
 
     /// The reply-to addresses of the email.
-    #[serde(rename = "replyTo", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "replyTo", skip_serializing_if = "is_none_or_empty")]
     pub reply_to: Option<Vec<EmailAddress>>,
 
     /// Indicates whether user engagement tracking is disabled.
+    ///
+    /// `None` omits the field, so ACS applies the resource's own engagement tracking setting.
+    /// `Some(true)` disables tracking for this email, and `Some(false)` requests tracking, which
+    /// only takes effect if tracking is enabled on the resource.
     #[serde(
         rename = "userEngagementTrackingDisabled",
         skip_serializing_if = "Option::is_none"
@@ -114,6 +271,183 @@ pub struct SentEmail {
     pub user_engagement_tracking_disabled: Option<bool>,
 }
 
+impl SentEmail {
+    /// Returns a copy of the email that is safe to log.
+    ///
+    /// Recipient and reply-to addresses are masked with `EmailAddress::masked`, and attachment
+    /// contents are replaced with their length.
+    ///
+    /// # Returns
+    ///
+    /// * `SentEmail` - The redacted email.
+    pub fn redacted(&self) -> SentEmail {
+        let mask_all = |list: &Option<Vec<EmailAddress>>| {
+            list.as_ref()
+                .map(|addresses| addresses.iter().map(EmailAddress::masked).collect())
+        };
+        SentEmail {
+            recipients: Recipients {
+                to: mask_all(&self.recipients.to),
+                cc: mask_all(&self.recipients.cc),
+                b_cc: mask_all(&self.recipients.b_cc),
+            },
+            reply_to: mask_all(&self.reply_to),
+            attachments: self.attachments.as_ref().map(|attachments| {
+                attachments
+                    .iter()
+                    .map(|attachment| EmailAttachment {
+                        content_bytes_base64: match &attachment.source {
+                            Some(AttachmentSource::InMemory(bytes)) => {
+                                Some(format!("<{} bytes>", bytes.len()))
+                            }
+                            Some(AttachmentSource::Path(path)) => {
+                                Some(format!("<contents of {}>", path.display()))
+                            }
+                            None => attachment
+                                .content_bytes_base64
+                                .as_ref()
+                                .map(|content| format!("<{} base64 characters>", content.len())),
+                        },
+                        name: attachment.name.clone(),
+                        attachment_type: attachment.attachment_type.clone(),
+                        source: None,
+                    })
+                    .collect()
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// Iterates over all recipients of the email, in to, cc, then bcc order.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Iterator<Item = &EmailAddress>` - The to, cc and bcc addresses.
+    pub fn all_recipients(&self) -> impl Iterator<Item = &EmailAddress> {
+        self.recipients.iter()
+    }
+
+    /// Counts the recipients of the email across the to, cc and bcc lists.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The total number of recipients.
+    pub fn recipient_count(&self) -> usize {
+        self.recipients.count()
+    }
+
+    /// Validates the content, recipients, headers and attachments of the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_attachments_size` - The maximum total size of the decoded attachments in bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), BuildError>` - The first problem found, if any.
+    pub fn validate(&self, max_attachments_size: usize) -> Result<(), BuildError> {
+        self.validate_structure()?;
+        if let Some(headers) = &self.headers {
+            validate_headers(headers)?;
+        }
+        if let Some(attachments) = &self.attachments {
+            validate_attachments(attachments, max_attachments_size)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the email has a subject, a body, at least one recipient and well-formed
+    /// sender, recipient and reply-to addresses.
+    ///
+    /// Unlike `validate`, headers and attachments aren't checked.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), BuildError>` - The first missing part or invalid address, if any.
+    pub(crate) fn validate_structure(&self) -> Result<(), BuildError> {
+        let is_blank = |value: &Option<String>| value.as_deref().is_none_or(str::is_empty);
+        if is_blank(&self.content.subject) {
+            return Err(BuildError::MissingSubject);
+        }
+        if is_blank(&self.content.plain_text) && is_blank(&self.content.html) {
+            return Err(BuildError::MissingBody);
+        }
+        if self.recipients.count() == 0 {
+            return Err(BuildError::NoRecipients);
+        }
+        // A blank sender is filled in with the client's default sender when sending
+        if !self.sender.trim().is_empty() && self.sender.parse::<EmailAddress>().is_err() {
+            return Err(BuildError::InvalidAddress(self.sender.clone()));
+        }
+        validate_address_list(&self.recipients.to)?;
+        validate_address_list(&self.recipients.cc)?;
+        validate_address_list(&self.recipients.b_cc)?;
+        validate_address_list(&self.reply_to)?;
+        Ok(())
+    }
+}
+
+/// An email stored for sending later, e.g. in a durable queue between a producer and a worker.
+///
+/// The `request_id` is sent as the repeatability request ID, so replaying the same queued email
+/// after a failure doesn't deliver it twice.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QueuedEmail {
+    /// The email to send.
+    #[serde(rename = "email")]
+    pub email: SentEmail,
+
+    /// The repeatability request ID used for every send attempt.
+    #[serde(rename = "requestId")]
+    pub request_id: String,
+
+    /// When the email was enqueued.
+    #[serde(rename = "enqueuedAt")]
+    pub enqueued_at: SystemTime,
+
+    /// When the email should be sent, `None` to send it as soon as possible.
+    #[serde(
+        rename = "scheduledAt",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub scheduled_at: Option<SystemTime>,
+}
+
+impl QueuedEmail {
+    /// Creates a `QueuedEmail` with a new request ID, enqueued now.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - The email to send.
+    ///
+    /// # Returns
+    ///
+    /// * `QueuedEmail` - The queued email, to be sent as soon as possible.
+    pub fn new(email: SentEmail) -> Self {
+        QueuedEmail {
+            email,
+            request_id: Uuid::new_v4().to_string(),
+            enqueued_at: SystemTime::now(),
+            scheduled_at: None,
+        }
+    }
+
+    /// Sets when the email should be sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheduled_at` - The time to send the email at.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The queued email.
+    pub fn scheduled_at(mut self, scheduled_at: SystemTime) -> Self {
+        self.scheduled_at = Some(scheduled_at);
+        self
+    }
+}
+
 /// Builder for creating a `SentEmail` instance.
 pub struct SentEmailBuilder {
     headers: Option<Vec<Header>>,
@@ -123,6 +457,16 @@ pub struct SentEmailBuilder {
     attachments: Option<Vec<EmailAttachment>>,
     reply_to: Option<Vec<EmailAddress>>,
     user_engagement_tracking_disabled: Option<bool>,
+    max_attachments_size: usize,
+    dedupe_recipients: bool,
+    max_html_size: usize,
+    strict_size: bool,
+}
+
+impl Default for SentEmailBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SentEmailBuilder {
@@ -140,6 +484,10 @@ impl SentEmailBuilder {
             attachments: None,
             reply_to: None,
             user_engagement_tracking_disabled: None,
+            max_attachments_size: DEFAULT_MAX_ATTACHMENTS_SIZE,
+            dedupe_recipients: false,
+            max_html_size: DEFAULT_MAX_HTML_SIZE,
+            strict_size: false,
         }
     }
 
@@ -152,14 +500,47 @@ impl SentEmailBuilder {
     /// # Returns
     ///
     /// * `Self` - The builder instance.
-    #[allow(dead_code)]
     pub fn headers(mut self, headers: Vec<Header>) -> Self {
         self.headers = Some(headers);
         self
     }
 
+    /// Adds a single custom header to the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header.
+    /// * `value` - The value of the header.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .get_or_insert_with(Vec::new)
+            .push(Header::new(name, value));
+        self
+    }
+
+    /// Adds the `List-Unsubscribe` and `List-Unsubscribe-Post` headers for one-click unsubscribe.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The HTTPS unsubscribe URL.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn list_unsubscribe(self, url: &str) -> Self {
+        self.header("List-Unsubscribe", &format!("<{}>", url))
+            .header("List-Unsubscribe-Post", "List-Unsubscribe=One-Click")
+    }
+
     /// Sets the sender address for the email.
     ///
+    /// When no sender is set, the email is sent from the client's default sender, see
+    /// `ACSClientBuilder::default_sender`.
+    ///
     /// # Arguments
     ///
     /// * `sender` - A string representing the sender address.
@@ -172,6 +553,39 @@ impl SentEmailBuilder {
         self
     }
 
+    /// Sets the sender address with a display name, e.g. `DoNotReply <donotreply@example.com>`.
+    ///
+    /// The display name is quoted when it contains special characters, like `EmailAddress`'s
+    /// `Display` implementation. `build()` rejects the sender if the address doesn't parse.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - A string slice that holds the sender address.
+    /// * `display_name` - A string slice that holds the sender's display name.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::SentEmailBuilder;
+    ///
+    /// let email = SentEmailBuilder::new()
+    ///     .sender_with_display_name("donotreply@example.com", "DoNotReply")
+    ///     .subject("Subject")
+    ///     .plain_text("Body")
+    ///     .to("jane@example.com")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(email.sender, "DoNotReply <donotreply@example.com>");
+    /// ```
+    pub fn sender_with_display_name(self, address: &str, display_name: &str) -> Self {
+        let sender = EmailAddress::from(address).with_display_name(display_name);
+        self.sender(sender.to_string())
+    }
+
     /// Sets the content for the email.
     ///
     /// # Arguments
@@ -186,6 +600,52 @@ impl SentEmailBuilder {
         self
     }
 
+    /// Sets the subject of the email, creating the content if it hasn't been set yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `subject` - A string slice representing the subject.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.content_mut().subject = Some(subject.to_string());
+        self
+    }
+
+    /// Sets the plain text body of the email, creating the content if it hasn't been set yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `plain_text` - A string slice representing the plain text body.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn plain_text(mut self, plain_text: &str) -> Self {
+        self.content_mut().plain_text = Some(plain_text.to_string());
+        self
+    }
+
+    /// Sets the HTML body of the email, creating the content if it hasn't been set yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `html` - A string slice representing the HTML body.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn html(mut self, html: &str) -> Self {
+        self.content_mut().html = Some(html.to_string());
+        self
+    }
+
+    fn content_mut(&mut self) -> &mut EmailContent {
+        self.content.get_or_insert_with(EmailContent::default)
+    }
+
     /// Sets the recipients for the email.
     ///
     /// # Arguments
@@ -200,6 +660,60 @@ impl SentEmailBuilder {
         self
     }
 
+    /// Adds a primary recipient to the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Anything convertible into an `EmailAddress`, e.g. `"jane@example.com"`.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn to(mut self, address: impl Into<EmailAddress>) -> Self {
+        let recipients = self.recipients.get_or_insert_with(Recipients::default);
+        recipients
+            .to
+            .get_or_insert_with(Vec::new)
+            .push(address.into());
+        self
+    }
+
+    /// Adds a CC recipient to the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Anything convertible into an `EmailAddress`, e.g. `"jane@example.com"`.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn cc(mut self, address: impl Into<EmailAddress>) -> Self {
+        let recipients = self.recipients.get_or_insert_with(Recipients::default);
+        recipients
+            .cc
+            .get_or_insert_with(Vec::new)
+            .push(address.into());
+        self
+    }
+
+    /// Adds a BCC recipient to the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Anything convertible into an `EmailAddress`, e.g. `"jane@example.com"`.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn bcc(mut self, address: impl Into<EmailAddress>) -> Self {
+        let recipients = self.recipients.get_or_insert_with(Recipients::default);
+        recipients
+            .b_cc
+            .get_or_insert_with(Vec::new)
+            .push(address.into());
+        self
+    }
+
     /// Sets the attachments for the email.
     ///
     /// # Arguments
@@ -209,7 +723,6 @@ impl SentEmailBuilder {
     /// # Returns
     ///
     /// * `Self` - The builder instance.
-    #[allow(dead_code)]
     pub fn attachments(mut self, attachments: Vec<EmailAttachment>) -> Self {
         self.attachments = Some(attachments);
         self
@@ -224,12 +737,42 @@ impl SentEmailBuilder {
     /// # Returns
     ///
     /// * `Self` - The builder instance.
-    #[allow(dead_code)]
     pub fn reply_to(mut self, reply_to: Vec<EmailAddress>) -> Self {
         self.reply_to = Some(reply_to);
         self
     }
 
+    /// Adds a single reply-to address for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `reply_to` - An `EmailAddress` instance.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn reply_to_one(mut self, reply_to: EmailAddress) -> Self {
+        self.reply_to.get_or_insert_with(Vec::new).push(reply_to);
+        self
+    }
+
+    /// Adds several reply-to addresses for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `reply_to` - The addresses to add.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn reply_to_all(
+        mut self,
+        reply_to: impl IntoIterator<Item = impl Into<EmailAddress>>,
+    ) -> Self {
+        extend_addresses(&mut self.reply_to, reply_to);
+        self
+    }
+
     /// Sets whether user engagement tracking is disabled for the email.
     ///
     /// # Arguments
@@ -240,35 +783,402 @@ impl SentEmailBuilder {
     ///
     /// * `Self` - The builder instance.
     pub fn user_engagement_tracking_disabled(
-        mut self,
+        self,
         user_engagement_tracking_disabled: bool,
     ) -> Self {
-        self.user_engagement_tracking_disabled = Some(user_engagement_tracking_disabled);
+        self.tracking(!user_engagement_tracking_disabled)
+    }
+
+    /// Sets whether user engagement tracking is requested for the email, overriding the resource
+    /// default. The other tracking methods all go through this one.
+    ///
+    /// ACS controls tracking only with the `userEngagementTrackingDisabled` field, there is no
+    /// header form that could disagree with it. Tracking is only applied if it is also enabled
+    /// on the resource.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to track opens and clicks.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::SentEmailBuilder;
+    ///
+    /// let email = SentEmailBuilder::new()
+    ///     .sender("donotreply@example.com".to_string())
+    ///     .subject("Subject")
+    ///     .plain_text("Body")
+    ///     .to("jane@example.com")
+    ///     .tracking(false)
+    ///     .build()
+    ///     .unwrap();
+    /// let json = serde_json::to_value(&email).unwrap();
+    /// assert_eq!(json["userEngagementTrackingDisabled"], true);
+    /// ```
+    pub fn tracking(mut self, enabled: bool) -> Self {
+        self.user_engagement_tracking_disabled = Some(!enabled);
         self
     }
 
-    /// Builds the `SentEmail` instance.
+    /// Disables user engagement tracking for the email, overriding the resource default.
     ///
     /// # Returns
     ///
-    /// * `Result<SentEmail, &\`static str\>` - The built `SentEmail` instance or an error message.
-    pub fn build(self) -> Result<SentEmail, &'static str> {
-        Ok(SentEmail {
-            headers: self.headers,
-            sender: self.sender.ok_or("Sender is required")?,
-            content: self.content.ok_or("Content is required")?,
-            recipients: self.recipients.ok_or("Recipients are required")?,
-            attachments: self.attachments,
-            reply_to: self.reply_to,
-            user_engagement_tracking_disabled: self.user_engagement_tracking_disabled,
-        })
+    /// * `Self` - The builder instance.
+    pub fn disable_tracking(self) -> Self {
+        self.tracking(false)
     }
-}
 
-/// Represents an email attachment.
-#[derive(Serialize, Deserialize, Debug)]
-pub struct EmailAttachment {
-    /// The name of the attachment.
+    /// Explicitly requests user engagement tracking for the email instead of the resource default.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn enable_tracking(self) -> Self {
+        self.tracking(true)
+    }
+
+    /// Sets the maximum total size of the decoded attachments in bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_attachments_size` - The size limit, defaults to `DEFAULT_MAX_ATTACHMENTS_SIZE`.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn max_attachments_size(mut self, max_attachments_size: usize) -> Self {
+        self.max_attachments_size = max_attachments_size;
+        self
+    }
+
+    /// Sets the HTML body size in bytes above which `build()` warns, or fails with `strict_size()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_html_size` - The size threshold, defaults to `DEFAULT_MAX_HTML_SIZE`.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn max_html_size(mut self, max_html_size: usize) -> Self {
+        self.max_html_size = max_html_size;
+        self
+    }
+
+    /// Makes `build()` fail instead of logging a warning when the HTML body is too large.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn strict_size(mut self) -> Self {
+        self.strict_size = true;
+        self
+    }
+
+    /// Removes duplicate recipient addresses when the email is built, see `Recipients::dedupe`.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn dedupe_recipients(mut self) -> Self {
+        self.dedupe_recipients = true;
+        self
+    }
+
+    /// Builds the `SentEmail` instance.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SentEmail, BuildError>` - The built `SentEmail` instance or the first problem found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::{BuildError, SentEmailBuilder};
+    ///
+    /// let result = SentEmailBuilder::new()
+    ///     .subject("Subject")
+    ///     .plain_text("Body")
+    ///     .to("not an address")
+    ///     .build();
+    /// assert_eq!(
+    ///     result.unwrap_err(),
+    ///     BuildError::InvalidAddress("not an address".to_string())
+    /// );
+    /// ```
+    pub fn build(self) -> Result<SentEmail, BuildError> {
+        let mut recipients = self.recipients.ok_or(BuildError::MissingRecipients)?;
+        if self.dedupe_recipients {
+            recipients = recipients.dedupe();
+        }
+        let email = SentEmail {
+            headers: self.headers,
+            sender: self.sender.unwrap_or_default(),
+            content: self.content.ok_or(BuildError::MissingContent)?,
+            recipients,
+            attachments: self.attachments,
+            reply_to: self.reply_to,
+            user_engagement_tracking_disabled: self.user_engagement_tracking_disabled,
+        };
+        email.validate(self.max_attachments_size)?;
+        let html_size = email.content.html.as_ref().map_or(0, String::len);
+        if html_size > self.max_html_size {
+            let error = BuildError::HtmlTooLarge {
+                size: html_size,
+                max_size: self.max_html_size,
+            };
+            if self.strict_size {
+                return Err(error);
+            }
+            warn!("{}", error);
+        }
+        Ok(email)
+    }
+}
+
+/// Marker for a required field that hasn't been set on a `TypedSentEmailBuilder`.
+#[derive(Debug, Default)]
+pub struct Missing;
+
+/// Builder for creating a `SentEmail` where the required fields are tracked at the type level.
+///
+/// `build()` is only available once `sender`, `content` and `recipients` have been set, so it
+/// can't fail at runtime. It doesn't validate the values themselves; use `try_build()` to also run
+/// the same checks as `SentEmailBuilder::build`.
+pub struct TypedSentEmailBuilder<S, C, R> {
+    sender: S,
+    content: C,
+    recipients: R,
+    headers: Option<Vec<Header>>,
+    attachments: Option<Vec<EmailAttachment>>,
+    reply_to: Option<Vec<EmailAddress>>,
+    user_engagement_tracking_disabled: Option<bool>,
+}
+
+impl Default for TypedSentEmailBuilder<Missing, Missing, Missing> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypedSentEmailBuilder<Missing, Missing, Missing> {
+    /// Creates a new `TypedSentEmailBuilder` instance.
+    ///
+    /// # Returns
+    ///
+    /// * `TypedSentEmailBuilder` - A new instance of the builder with no required field set.
+    pub fn new() -> Self {
+        TypedSentEmailBuilder {
+            sender: Missing,
+            content: Missing,
+            recipients: Missing,
+            headers: None,
+            attachments: None,
+            reply_to: None,
+            user_engagement_tracking_disabled: None,
+        }
+    }
+}
+
+impl<S, C, R> TypedSentEmailBuilder<S, C, R> {
+    /// Sets the sender address for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - A string representing the sender address.
+    ///
+    /// # Returns
+    ///
+    /// * `TypedSentEmailBuilder` - The builder with the sender set.
+    pub fn sender(self, sender: String) -> TypedSentEmailBuilder<String, C, R> {
+        TypedSentEmailBuilder {
+            sender,
+            content: self.content,
+            recipients: self.recipients,
+            headers: self.headers,
+            attachments: self.attachments,
+            reply_to: self.reply_to,
+            user_engagement_tracking_disabled: self.user_engagement_tracking_disabled,
+        }
+    }
+
+    /// Sets the content for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - An `EmailContent` instance.
+    ///
+    /// # Returns
+    ///
+    /// * `TypedSentEmailBuilder` - The builder with the content set.
+    pub fn content(self, content: EmailContent) -> TypedSentEmailBuilder<S, EmailContent, R> {
+        TypedSentEmailBuilder {
+            sender: self.sender,
+            content,
+            recipients: self.recipients,
+            headers: self.headers,
+            attachments: self.attachments,
+            reply_to: self.reply_to,
+            user_engagement_tracking_disabled: self.user_engagement_tracking_disabled,
+        }
+    }
+
+    /// Sets the recipients for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `recipients` - A `Recipients` instance.
+    ///
+    /// # Returns
+    ///
+    /// * `TypedSentEmailBuilder` - The builder with the recipients set.
+    pub fn recipients(self, recipients: Recipients) -> TypedSentEmailBuilder<S, C, Recipients> {
+        TypedSentEmailBuilder {
+            sender: self.sender,
+            content: self.content,
+            recipients,
+            headers: self.headers,
+            attachments: self.attachments,
+            reply_to: self.reply_to,
+            user_engagement_tracking_disabled: self.user_engagement_tracking_disabled,
+        }
+    }
+
+    /// Sets the headers for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - A vector of `Header` instances.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn headers(mut self, headers: Vec<Header>) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+
+    /// Sets the attachments for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `attachments` - A vector of `EmailAttachment` instances.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn attachments(mut self, attachments: Vec<EmailAttachment>) -> Self {
+        self.attachments = Some(attachments);
+        self
+    }
+
+    /// Sets the reply-to addresses for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `reply_to` - A vector of `EmailAddress` instances.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn reply_to(mut self, reply_to: Vec<EmailAddress>) -> Self {
+        self.reply_to = Some(reply_to);
+        self
+    }
+
+    /// Sets whether user engagement tracking is disabled for the email.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_engagement_tracking_disabled` - A boolean indicating whether tracking is disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn user_engagement_tracking_disabled(
+        self,
+        user_engagement_tracking_disabled: bool,
+    ) -> Self {
+        self.tracking(!user_engagement_tracking_disabled)
+    }
+
+    /// Sets whether user engagement tracking is requested for the email, like
+    /// [`SentEmailBuilder::tracking`]. The other tracking methods all go through this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to track opens and clicks.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn tracking(mut self, enabled: bool) -> Self {
+        self.user_engagement_tracking_disabled = Some(!enabled);
+        self
+    }
+
+    /// Disables user engagement tracking for the email, overriding the resource default.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn disable_tracking(self) -> Self {
+        self.tracking(false)
+    }
+
+    /// Explicitly requests user engagement tracking for the email instead of the resource default.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The builder instance.
+    pub fn enable_tracking(self) -> Self {
+        self.tracking(true)
+    }
+}
+
+impl TypedSentEmailBuilder<String, EmailContent, Recipients> {
+    /// Builds the `SentEmail` instance.
+    ///
+    /// # Returns
+    ///
+    /// * `SentEmail` - The built `SentEmail` instance.
+    pub fn build(self) -> SentEmail {
+        SentEmail {
+            headers: self.headers,
+            sender: self.sender,
+            content: self.content,
+            recipients: self.recipients,
+            attachments: self.attachments,
+            reply_to: self.reply_to,
+            user_engagement_tracking_disabled: self.user_engagement_tracking_disabled,
+        }
+    }
+
+    /// Builds the `SentEmail` instance and validates it like `SentEmailBuilder::build`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SentEmail, BuildError>` - The built `SentEmail` instance or the first problem found.
+    pub fn try_build(self) -> Result<SentEmail, BuildError> {
+        let email = self.build();
+        email.validate(DEFAULT_MAX_ATTACHMENTS_SIZE)?;
+        Ok(email)
+    }
+}
+
+/// Represents an email attachment.
+///
+/// Serialized by hand, so a lazy `AttachmentSource` is only read and encoded when the request
+/// body is built.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmailAttachment {
+    /// The name of the attachment.
     #[serde(rename = "name")]
     name: Option<String>,
 
@@ -276,73 +1186,1271 @@ pub struct EmailAttachment {
     #[serde(rename = "contentType")]
     attachment_type: Option<String>,
 
-    /// The base64 encoded content of the attachment.
-    #[serde(rename = "contentInBase64")]
-    content_bytes_base64: Option<String>,
+    /// The base64 encoded content of the attachment.
+    #[serde(rename = "contentInBase64")]
+    content_bytes_base64: Option<String>,
+
+    /// The raw content, encoded only when serialized. Takes precedence over `content_bytes_base64`.
+    #[serde(skip)]
+    source: Option<AttachmentSource>,
+}
+
+/// The raw content of an attachment that is base64 encoded only when the email is serialized.
+///
+/// An email holding a `Path` source costs no memory for the attachment until it's sent, which
+/// helps when many emails with large generated reports are queued at once. The JSON request body
+/// still holds the whole encoded file while a send is in flight, so this lowers the memory held
+/// between building and sending, not the peak per send. The file is read again on every retry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttachmentSource {
+    /// Raw bytes held in memory.
+    InMemory(Vec<u8>),
+
+    /// A file read in chunks when the email is serialized.
+    Path(PathBuf),
+}
+
+impl AttachmentSource {
+    // The raw size of the content, before base64 encoding
+    fn len(&self) -> Result<u64, String> {
+        match self {
+            AttachmentSource::InMemory(bytes) => Ok(bytes.len() as u64),
+            AttachmentSource::Path(path) => fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e)),
+        }
+    }
+}
+
+// Writes the base64 encoding of a source, reading files in chunks. A read error is kept in
+// `error`, since `fmt::Error` can't carry it.
+struct Base64Source<'a> {
+    source: &'a AttachmentSource,
+    error: RefCell<Option<String>>,
+}
+
+impl fmt::Display for Base64Source<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let path = match self.source {
+            AttachmentSource::InMemory(bytes) => {
+                return f.write_str(&general_purpose::STANDARD.encode(bytes))
+            }
+            AttachmentSource::Path(path) => path,
+        };
+        let fail = |e: std::io::Error| {
+            *self.error.borrow_mut() = Some(format!(
+                "Failed to read attachment {}: {}",
+                path.display(),
+                e
+            ));
+            fmt::Error
+        };
+        let mut file = fs::File::open(path).map_err(fail)?;
+        // A multiple of 3 bytes, so only the last chunk's encoding is padded
+        let mut chunk = vec![0; 3 * 16 * 1024];
+        loop {
+            let mut len = 0;
+            while len < chunk.len() {
+                match file.read(&mut chunk[len..]).map_err(fail)? {
+                    0 => break,
+                    read => len += read,
+                }
+            }
+            f.write_str(&general_purpose::STANDARD.encode(&chunk[..len]))?;
+            if len < chunk.len() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Serialize for EmailAttachment {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Content<'a>(&'a EmailAttachment);
+
+        impl Serialize for Content<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let Some(source) = &self.0.source else {
+                    return self.0.content_bytes_base64.serialize(serializer);
+                };
+                let base64 = Base64Source {
+                    source,
+                    error: RefCell::new(None),
+                };
+                serializer
+                    .collect_str(&base64)
+                    .map_err(|e| match base64.error.take() {
+                        Some(message) => serde::ser::Error::custom(message),
+                        None => e,
+                    })
+            }
+        }
+
+        let mut state = serializer.serialize_struct("EmailAttachment", 3)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("contentType", &self.attachment_type)?;
+        state.serialize_field("contentInBase64", &Content(self))?;
+        state.end()
+    }
+}
+
+impl EmailAttachment {
+    /// Returns the file name of the attachment.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the MIME type of the attachment.
+    pub fn attachment_type(&self) -> Option<&str> {
+        self.attachment_type.as_deref()
+    }
+
+    /// Returns the base64 encoded content of the attachment, `None` for an `AttachmentSource`.
+    pub fn content_bytes_base64(&self) -> Option<&str> {
+        self.content_bytes_base64.as_deref()
+    }
+
+    /// Returns the lazily encoded content of the attachment, if it was created from a source.
+    pub fn source(&self) -> Option<&AttachmentSource> {
+        self.source.as_ref()
+    }
+
+    /// Creates an attachment whose content is read and base64 encoded only when it's serialized.
+    ///
+    /// A missing or unreadable file is reported when the email is validated or sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The file name of the attachment.
+    /// * `content_type` - The MIME content type, e.g. `application/pdf`.
+    /// * `source` - The raw content or the file holding it.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailAttachment` - The attachment.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::{AttachmentSource, EmailAttachment};
+    ///
+    /// let content = vec![7u8; 100_000];
+    /// let path = std::env::temp_dir().join("azure_email_service_report.bin");
+    /// std::fs::write(&path, &content).unwrap();
+    ///
+    /// let from_path = EmailAttachment::from_source(
+    ///     "report.bin",
+    ///     "application/octet-stream",
+    ///     AttachmentSource::Path(path.clone()),
+    /// );
+    /// let in_memory =
+    ///     EmailAttachment::from_bytes("report.bin", "application/octet-stream", &content);
+    /// assert_eq!(
+    ///     serde_json::to_string(&from_path).unwrap(),
+    ///     serde_json::to_string(&in_memory).unwrap()
+    /// );
+    /// std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn from_source(name: &str, content_type: &str, source: AttachmentSource) -> Self {
+        EmailAttachment {
+            name: Some(name.to_string()),
+            attachment_type: Some(content_type.to_string()),
+            content_bytes_base64: None,
+            source: Some(source),
+        }
+    }
+
+    // The raw size of the content, decoding it if it's held in base64
+    fn decoded_size(&self) -> Result<u64, BuildError> {
+        let size = match &self.source {
+            Some(source) => source.len(),
+            None => general_purpose::STANDARD
+                .decode(self.content_bytes_base64.as_deref().unwrap_or_default())
+                .map(|content| content.len() as u64)
+                .map_err(|e| format!("not valid base64: {}", e)),
+        };
+        size.map_err(|reason| BuildError::InvalidAttachment {
+            name: self.name.as_deref().unwrap_or("<unnamed>").to_string(),
+            reason,
+        })
+    }
+
+    /// Creates an attachment from raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The file name of the attachment.
+    /// * `content_type` - The MIME content type, e.g. `application/pdf`.
+    /// * `content` - The raw content, which is base64 encoded.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailAttachment` - The attachment with its content base64 encoded.
+    pub fn from_bytes(name: &str, content_type: &str, content: &[u8]) -> Self {
+        EmailAttachment {
+            name: Some(name.to_string()),
+            attachment_type: Some(content_type.to_string()),
+            content_bytes_base64: Some(general_purpose::STANDARD.encode(content)),
+            source: None,
+        }
+    }
+
+    /// Creates an attachment from a file, guessing the content type from its extension.
+    ///
+    /// Files larger than `DEFAULT_MAX_ENCODED_ATTACHMENT_SIZE` once base64 encoded are rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to attach.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<EmailAttachment, String>` - The attachment or an error message.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_path_with_limit(path, DEFAULT_MAX_ENCODED_ATTACHMENT_SIZE)
+    }
+
+    /// Creates an attachment from a file, rejecting it before reading if it's too large.
+    ///
+    /// Base64 inflates content by about a third, so the limit applies to the encoded size.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to attach.
+    /// * `max_encoded_size` - The maximum base64 encoded size of the attachment in bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<EmailAttachment, String>` - The attachment or an error message.
+    pub fn from_path_with_limit(
+        path: impl AsRef<Path>,
+        max_encoded_size: usize,
+    ) -> Result<Self, String> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("Invalid attachment path: {}", path.display()))?;
+        let size = fs::metadata(path)
+            .map_err(|e| format!("Failed to read attachment {}: {}", path.display(), e))?
+            .len();
+        let encoded_size = usize::try_from(size)
+            .ok()
+            .and_then(|size| size.div_ceil(3).checked_mul(4))
+            .unwrap_or(usize::MAX);
+        if encoded_size > max_encoded_size {
+            return Err(format!(
+                "Attachment {} would be {} bytes once base64 encoded, over the {} byte limit (ACS caps the total message size at 10 MB)",
+                path.display(),
+                encoded_size,
+                max_encoded_size
+            ));
+        }
+        let content = fs::read(path)
+            .map_err(|e| format!("Failed to read attachment {}: {}", path.display(), e))?;
+        Ok(Self::from_bytes(name, guess_content_type(path), &content))
+    }
+
+    /// Creates a `text/calendar` attachment for a meeting invite.
+    ///
+    /// The content type is set to `text/calendar; method=REQUEST` so that Outlook renders the
+    /// attachment as an invite. Keep a plain text and/or HTML body on the email as well: ACS
+    /// sends the calendar part as an attachment alongside those bodies, and clients that don't
+    /// understand the invite fall back to showing the regular content.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The file name of the attachment, e.g. `invite.ics`.
+    /// * `ics` - The iCalendar content.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailAttachment` - The attachment with its content base64 encoded.
+    pub fn calendar(name: &str, ics: &str) -> Self {
+        EmailAttachment {
+            name: Some(name.to_string()),
+            attachment_type: Some("text/calendar; method=REQUEST".to_string()),
+            content_bytes_base64: Some(general_purpose::STANDARD.encode(ics)),
+            source: None,
+        }
+    }
+}
+
+/// Guesses the MIME content type of a file from its extension.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+///
+/// # Returns
+///
+/// * `&'static str` - The content type, `application/octet-stream` if the extension is unknown.
+fn guess_content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "txt" => "text/plain",
+        "htm" | "html" => "text/html",
+        "csv" => "text/csv",
+        "ics" => "text/calendar",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Validates that the custom header names are accepted by ACS.
+///
+/// ACS only accepts `x-` prefixed custom headers and a few standard headers
+/// (see `ALLOWED_STANDARD_HEADERS`).
+///
+/// # Arguments
+///
+/// * `headers` - A slice of `Header` instances.
+///
+/// # Returns
+///
+/// * `Result<(), BuildError>` - An `InvalidHeader` error naming the disallowed header, if any.
+pub fn validate_headers(headers: &[Header]) -> Result<(), BuildError> {
+    for header in headers {
+        let name = header.name.as_deref().unwrap_or_default();
+        let is_valid_name =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_graphic() && c != ':');
+        let is_allowed = name.len() > 2
+            && name
+                .get(..2)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("x-"))
+            || ALLOWED_STANDARD_HEADERS
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(name));
+        if !is_valid_name || !is_allowed {
+            return Err(BuildError::InvalidHeader(name.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Validates the content type and the total decoded size of the attachments.
+///
+/// # Arguments
+///
+/// * `attachments` - A slice of `EmailAttachment` instances.
+/// * `max_size` - The maximum total size of the decoded attachments in bytes.
+///
+/// # Returns
+///
+/// * `Result<(), BuildError>` - An error naming the offending attachment, if any.
+pub fn validate_attachments(
+    attachments: &[EmailAttachment],
+    max_size: usize,
+) -> Result<(), BuildError> {
+    let mut total_size = 0;
+    for attachment in attachments {
+        let name = attachment.name.as_deref().unwrap_or("<unnamed>");
+
+        let content_type = attachment.attachment_type.as_deref().unwrap_or_default();
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+        let is_valid_type = match mime_type.split_once('/') {
+            Some((main_type, sub_type)) => {
+                !main_type.is_empty()
+                    && !sub_type.is_empty()
+                    && !sub_type.contains('/')
+                    && !mime_type.contains(char::is_whitespace)
+            }
+            None => false,
+        };
+        if !is_valid_type {
+            return Err(BuildError::InvalidAttachment {
+                name: name.to_string(),
+                reason: format!("invalid content type '{}'", content_type),
+            });
+        }
+
+        let size = usize::try_from(attachment.decoded_size()?).unwrap_or(usize::MAX);
+        total_size = usize::saturating_add(total_size, size);
+        if total_size > max_size {
+            return Err(BuildError::AttachmentTooLarge {
+                name: name.to_string(),
+                size,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Represents the content of an email.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct EmailContent {
+    /// The subject of the email.
+    #[serde(rename = "subject")]
+    pub subject: Option<String>,
+
+    /// The plain text content of the email.
+    #[serde(rename = "plainText")]
+    pub plain_text: Option<String>,
+
+    /// The HTML content of the email.
+    #[serde(rename = "html")]
+    pub html: Option<String>,
+}
+
+impl EmailContent {
+    /// Creates a plain text only email content.
+    ///
+    /// # Arguments
+    ///
+    /// * `subject` - The subject of the email.
+    /// * `plain_text` - The plain text body of the email.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailContent` - The email content.
+    pub fn text(subject: &str, plain_text: &str) -> Self {
+        EmailContent {
+            subject: Some(subject.to_string()),
+            plain_text: Some(plain_text.to_string()),
+            html: None,
+        }
+    }
+
+    /// Creates an HTML only email content.
+    ///
+    /// # Arguments
+    ///
+    /// * `subject` - The subject of the email.
+    /// * `html` - The HTML body of the email.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailContent` - The email content.
+    pub fn html(subject: &str, html: &str) -> Self {
+        EmailContent {
+            subject: Some(subject.to_string()),
+            plain_text: None,
+            html: Some(html.to_string()),
+        }
+    }
+
+    /// Creates an email content with both a plain text and an HTML body.
+    ///
+    /// # Arguments
+    ///
+    /// * `subject` - The subject of the email.
+    /// * `plain_text` - The plain text body of the email.
+    /// * `html` - The HTML body of the email.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailContent` - The email content.
+    pub fn both(subject: &str, plain_text: &str, html: &str) -> Self {
+        EmailContent {
+            subject: Some(subject.to_string()),
+            plain_text: Some(plain_text.to_string()),
+            html: Some(html.to_string()),
+        }
+    }
+
+    /// Replaces `{{key}}` placeholders in the subject and bodies with the given values.
+    ///
+    /// Substituted values are inserted as is, without HTML escaping, and are not searched for
+    /// further placeholders. Whitespace inside the braces is ignored, so `{{ name }}` matches `name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The value of each placeholder key.
+    /// * `strict` - Whether a placeholder without a value is an error, rather than left as is.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<EmailContent, String>` - The personalized content, or an error naming the first
+    ///   placeholder without a value in strict mode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::EmailContent;
+    /// use std::collections::HashMap;
+    ///
+    /// let template = EmailContent::text("Hi {{name}}", "Your code is {{ code }}. {{unknown}}");
+    /// let values = HashMap::from([
+    ///     ("name".to_string(), "Jane".to_string()),
+    ///     ("code".to_string(), "1234".to_string()),
+    /// ]);
+    ///
+    /// let content = template.personalize(&values, false).unwrap();
+    /// assert_eq!(content.subject.as_deref(), Some("Hi Jane"));
+    /// assert_eq!(content.plain_text.as_deref(), Some("Your code is 1234. {{unknown}}"));
+    /// assert!(template.personalize(&values, true).is_err());
+    /// ```
+    pub fn personalize(
+        &self,
+        values: &HashMap<String, String>,
+        strict: bool,
+    ) -> Result<EmailContent, String> {
+        let substitute = |text: &Option<String>| {
+            text.as_deref()
+                .map(|text| substitute_placeholders(text, values, strict))
+                .transpose()
+        };
+        Ok(EmailContent {
+            subject: substitute(&self.subject)?,
+            plain_text: substitute(&self.plain_text)?,
+            html: substitute(&self.html)?,
+        })
+    }
+}
+
+// Replaces `{{key}}` placeholders in a single pass, so values can't inject further placeholders
+fn substitute_placeholders(
+    text: &str,
+    values: &HashMap<String, String>,
+    strict: bool,
+) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + 2 + len + 2];
+        let key = rest[start + 2..start + 2 + len].trim();
+        result.push_str(&rest[..start]);
+        match values.get(key) {
+            Some(value) => result.push_str(value),
+            None if strict => return Err(format!("No value for placeholder '{}'", placeholder)),
+            None => result.push_str(placeholder),
+        }
+        rest = &rest[start + placeholder.len()..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Represents a header in an email.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The name of the header.
+    #[serde(rename = "name")]
+    name: Option<String>,
+
+    /// The value of the header.
+    #[serde(rename = "value")]
+    value: Option<String>,
+}
+
+impl Header {
+    /// Creates a header.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header, e.g. `x-campaign-id`.
+    /// * `value` - The value of the header.
+    ///
+    /// # Returns
+    ///
+    /// * `Header` - The header.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::Header;
+    ///
+    /// let header = Header::new("x-campaign-id", "spring-sale");
+    /// assert_eq!(header.name(), Some("x-campaign-id"));
+    /// assert_eq!(header.value(), Some("spring-sale"));
+    /// ```
+    pub fn new(name: &str, value: &str) -> Self {
+        Header {
+            name: Some(name.to_string()),
+            value: Some(value.to_string()),
+        }
+    }
+
+    /// Returns the name of the header.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the value of the header.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+/// Represents the recipients of an email.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Recipients {
+    /// The primary recipients of the email.
+    #[serde(rename = "to")]
+    pub to: Option<Vec<EmailAddress>>,
+
+    /// The CC recipients of the email.
+    #[serde(rename = "cc")]
+    pub cc: Option<Vec<EmailAddress>>,
+
+    /// The BCC recipients of the email.
+    #[serde(rename = "bcc")]
+    pub b_cc: Option<Vec<EmailAddress>>,
+}
+
+impl Recipients {
+    /// Creates an empty set of recipients.
+    ///
+    /// # Returns
+    ///
+    /// * `Recipients` - Recipients with no to, cc or bcc addresses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds primary recipients, e.g. addresses read from a CSV file.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses` - The addresses to add.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The recipients.
+    pub fn to_all(mut self, addresses: impl IntoIterator<Item = impl Into<EmailAddress>>) -> Self {
+        extend_addresses(&mut self.to, addresses);
+        self
+    }
+
+    /// Adds CC recipients.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses` - The addresses to add.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The recipients.
+    pub fn cc_all(mut self, addresses: impl IntoIterator<Item = impl Into<EmailAddress>>) -> Self {
+        extend_addresses(&mut self.cc, addresses);
+        self
+    }
+
+    /// Adds BCC recipients.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses` - The addresses to add.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The recipients.
+    pub fn bcc_all(mut self, addresses: impl IntoIterator<Item = impl Into<EmailAddress>>) -> Self {
+        extend_addresses(&mut self.b_cc, addresses);
+        self
+    }
+
+    /// Combines two sets of recipients, appending the other's to, cc and bcc lists to these.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The recipients to add.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The combined recipients.
+    pub fn merge(self, other: Recipients) -> Self {
+        self.to_all(other.to.unwrap_or_default())
+            .cc_all(other.cc.unwrap_or_default())
+            .bcc_all(other.b_cc.unwrap_or_default())
+    }
+
+    /// Removes duplicate addresses within and across the to, cc and bcc lists.
+    ///
+    /// Addresses are compared case-insensitively and the first occurrence is kept, checking to,
+    /// then cc, then bcc, so an address in both to and bcc stays in to. The order of the
+    /// remaining addresses is preserved, and a list left empty is removed.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The recipients without duplicates.
+    pub fn dedupe(self) -> Self {
+        let mut seen = HashSet::new();
+        let mut dedupe_list = |list: Option<Vec<EmailAddress>>| {
+            let list: Vec<EmailAddress> = list?
+                .into_iter()
+                .filter(|address| match &address.email {
+                    Some(email) => seen.insert(email.to_lowercase()),
+                    None => true,
+                })
+                .collect();
+            (!list.is_empty()).then_some(list)
+        };
+        Recipients {
+            to: dedupe_list(self.to),
+            cc: dedupe_list(self.cc),
+            b_cc: dedupe_list(self.b_cc),
+        }
+    }
+
+    /// Iterates over the to, cc and bcc addresses, in that order.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Iterator<Item = &EmailAddress>` - The addresses of all lists that are set.
+    pub fn iter(&self) -> impl Iterator<Item = &EmailAddress> {
+        self.into_iter()
+    }
+
+    /// Counts the recipients across the to, cc and bcc lists.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The total number of recipients.
+    pub fn count(&self) -> usize {
+        [&self.to, &self.cc, &self.b_cc]
+            .iter()
+            .map(|list| list.as_ref().map_or(0, Vec::len))
+            .sum()
+    }
+}
+
+// Checks that an address has a single `@` between a non-empty local part and domain
+fn is_valid_address(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.is_empty()
+                && !domain.contains('@')
+                && !email.contains(|c: char| c.is_whitespace() || c == '<' || c == '>')
+        }
+        None => false,
+    }
+}
+
+/// Validates every address in an optional address list.
+///
+/// # Arguments
+///
+/// * `addresses` - The addresses to validate.
+///
+/// # Returns
+///
+/// * `Result<(), BuildError>` - An `InvalidAddress` error holding the first invalid address, if any.
+fn validate_address_list(addresses: &Option<Vec<EmailAddress>>) -> Result<(), BuildError> {
+    for address in addresses.iter().flatten() {
+        let email = address.email.as_deref().unwrap_or_default();
+        if !is_valid_address(email) {
+            return Err(BuildError::InvalidAddress(email.to_string()));
+        }
+    }
+    Ok(())
+}
+
+// Skips serializing optional lists that are unset or empty
+fn is_none_or_empty<T>(list: &Option<Vec<T>>) -> bool {
+    list.as_ref().is_none_or(Vec::is_empty)
+}
+
+// Appends addresses to a recipient list, leaving it unset if there are none
+fn extend_addresses(
+    list: &mut Option<Vec<EmailAddress>>,
+    addresses: impl IntoIterator<Item = impl Into<EmailAddress>>,
+) {
+    let mut addresses = addresses.into_iter().map(Into::into).peekable();
+    if addresses.peek().is_some() {
+        list.get_or_insert_with(Vec::new).extend(addresses);
+    }
+}
+
+/// Represents an email address.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmailAddress {
+    /// The email address.
+    #[serde(rename = "address")]
+    pub email: Option<String>,
+
+    /// The display name associated with the email address.
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+}
+
+impl EmailAddress {
+    /// Returns a copy of the address that is safe to log.
+    ///
+    /// Only the first character of the local part and the domain are kept, e.g.
+    /// `j***@contoso.com`, and the display name is replaced with `***`.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailAddress` - The masked address.
+    pub fn masked(&self) -> EmailAddress {
+        let email = self
+            .email
+            .as_deref()
+            .map(|email| match email.rsplit_once('@') {
+                Some((local, domain)) => {
+                    let first = local.chars().next().map(String::from).unwrap_or_default();
+                    format!("{}***@{}", first, domain)
+                }
+                None => "***".to_string(),
+            });
+        EmailAddress {
+            email,
+            display_name: self.display_name.as_ref().map(|_| "***".to_string()),
+        }
+    }
+
+    /// Sets the display name associated with the email address.
+    ///
+    /// # Arguments
+    ///
+    /// * `display_name` - The display name, e.g. `Jane Doe`.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The email address with the display name set.
+    pub fn with_display_name(mut self, display_name: &str) -> Self {
+        self.display_name = Some(display_name.to_string());
+        self
+    }
+}
+
+impl<'a> IntoIterator for &'a Recipients {
+    type Item = &'a EmailAddress;
+    type IntoIter = Flatten<Flatten<std::array::IntoIter<&'a Option<Vec<EmailAddress>>, 3>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [&self.to, &self.cc, &self.b_cc]
+            .into_iter()
+            .flatten()
+            .flatten()
+    }
+}
+
+impl From<&str> for EmailAddress {
+    fn from(email: &str) -> Self {
+        EmailAddress {
+            email: Some(email.to_string()),
+            display_name: None,
+        }
+    }
+}
+
+impl From<String> for EmailAddress {
+    fn from(email: String) -> Self {
+        EmailAddress {
+            email: Some(email),
+            display_name: None,
+        }
+    }
+}
+
+/// Represents an error response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ErrorResponse {
+    /// The error details.
+    #[serde(rename = "error")]
+    pub error: Option<ErrorDetail>,
+}
+
+/// Represents a page of domains linked to an Email Communication Service resource.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DomainResourceList {
+    /// The domains in this page.
+    #[serde(rename = "value")]
+    pub value: Option<Vec<DomainResource>>,
+
+    /// The URL of the next page, if any.
+    #[serde(rename = "nextLink")]
+    pub next_link: Option<String>,
+}
+
+/// Represents a domain of an Email Communication Service resource.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DomainResource {
+    /// The name of the domain resource, `AzureManagedDomain` for Azure managed domains.
+    #[serde(rename = "name")]
+    pub name: Option<String>,
+
+    /// The properties of the domain.
+    #[serde(rename = "properties")]
+    pub properties: Option<DomainProperties>,
+}
+
+/// Represents the properties of a domain resource.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DomainProperties {
+    /// The provisioning state of the domain, `Succeeded` once it is provisioned.
+    #[serde(rename = "provisioningState")]
+    pub provisioning_state: Option<String>,
+
+    /// The domain used in sender addresses.
+    #[serde(rename = "fromSenderDomain")]
+    pub from_sender_domain: Option<String>,
+}
+
+/// The Event Grid event type of ACS email delivery reports.
+pub const DELIVERY_REPORT_EVENT_TYPE: &str = "Microsoft.Communication.EmailDeliveryReportReceived";
+
+/// Represents an ACS email delivery report, the data of a
+/// `Microsoft.Communication.EmailDeliveryReportReceived` Event Grid event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeliveryReport {
+    /// The sender address of the email.
+    #[serde(rename = "sender")]
+    pub sender: Option<String>,
+
+    /// The recipient the report is for.
+    #[serde(rename = "recipient")]
+    pub recipient: Option<String>,
+
+    /// The operation ID returned when the email was sent.
+    #[serde(rename = "messageId")]
+    pub message_id: Option<String>,
+
+    /// The `Message-ID` header of the delivered email.
+    #[serde(rename = "internetMessageId")]
+    pub internet_message_id: Option<String>,
+
+    /// The delivery status for the recipient.
+    #[serde(rename = "status")]
+    pub status: Option<DeliveryStatus>,
+
+    /// Details about the delivery status, e.g. why it failed.
+    #[serde(rename = "deliveryStatusDetails")]
+    pub delivery_status_details: Option<DeliveryStatusDetails>,
+
+    /// The time of the delivery attempt, as an ISO 8601 timestamp.
+    #[serde(rename = "deliveryAttemptTimeStamp")]
+    pub delivery_attempt_timestamp: Option<String>,
+}
+
+impl DeliveryReport {
+    /// Parses a delivery report from an Event Grid or CloudEvents event.
+    ///
+    /// Accepts a single event, a batch with one event as delivered to webhooks, or the bare
+    /// event data. Events of other types are rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - A string slice that holds the event JSON.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DeliveryReport, String>` - The delivery report or an error message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::{DeliveryReport, EmailSendStatusType};
+    ///
+    /// let event = r#"[{
+    ///   "id": "00000000-0000-0000-0000-000000000000",
+    ///   "topic": "/subscriptions/{subscription-id}/resourceGroups/{group-name}/providers/microsoft.communication/communicationservices/{communication-services-resource-name}",
+    ///   "subject": "sender/senderid@azure.com/message/00000000-0000-0000-0000-000000000000",
+    ///   "data": {
+    ///     "sender": "senderid@azure.com",
+    ///     "recipient": "receiver@azure.com",
+    ///     "messageId": "00000000-0000-0000-0000-000000000000",
+    ///     "status": "Delivered",
+    ///     "deliveryStatusDetails": { "statusMessage": "Status Message" },
+    ///     "deliveryAttemptTimeStamp": "2020-09-18T00:22:20.2855749+00:00"
+    ///   },
+    ///   "eventType": "Microsoft.Communication.EmailDeliveryReportReceived",
+    ///   "dataVersion": "1.0",
+    ///   "metadataVersion": "1",
+    ///   "eventTime": "2020-09-18T00:22:20Z"
+    /// }]"#;
+    /// let report = DeliveryReport::from_event_json(event).unwrap();
+    /// assert_eq!(report.recipient.as_deref(), Some("receiver@azure.com"));
+    /// assert_eq!(report.send_status(), EmailSendStatusType::Succeeded);
+    /// ```
+    pub fn from_event_json(json: &str) -> Result<Self, String> {
+        let mut event: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid event JSON: {}", e))?;
+        if let serde_json::Value::Array(events) = event {
+            let [single]: [serde_json::Value; 1] = events
+                .try_into()
+                .map_err(|_| "Expected a batch with exactly one event".to_string())?;
+            event = single;
+        }
+        // Event Grid uses `eventType`, CloudEvents `type`
+        let event_type = event
+            .get("eventType")
+            .or_else(|| event.get("type"))
+            .and_then(|value| value.as_str());
+        if let Some(event_type) = event_type {
+            if event_type != DELIVERY_REPORT_EVENT_TYPE {
+                return Err(format!("Unexpected event type: {}", event_type));
+            }
+        }
+        let data = match event.get_mut("data") {
+            Some(data) => data.take(),
+            None => event,
+        };
+        serde_json::from_value(data).map_err(|e| format!("Invalid delivery report: {}", e))
+    }
+
+    /// Maps the delivery status to the corresponding send status.
+    ///
+    /// # Returns
+    ///
+    /// * `EmailSendStatusType` - `Succeeded` for delivered or expanded emails, `Failed` for
+    ///   failed, quarantined, spam-filtered or suppressed ones, and `Unknown` otherwise.
+    pub fn send_status(&self) -> EmailSendStatusType {
+        match self.status {
+            Some(DeliveryStatus::Delivered | DeliveryStatus::Expanded) => {
+                EmailSendStatusType::Succeeded
+            }
+            Some(
+                DeliveryStatus::Failed
+                | DeliveryStatus::Quarantined
+                | DeliveryStatus::FilteredSpam
+                | DeliveryStatus::Suppressed,
+            ) => EmailSendStatusType::Failed,
+            Some(DeliveryStatus::Unknown) | None => EmailSendStatusType::Unknown,
+        }
+    }
+}
+
+/// Enum representing the delivery statuses reported for a recipient.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum DeliveryStatus {
+    Delivered,
+    Expanded,
+    Failed,
+    Quarantined,
+    FilteredSpam,
+    Suppressed,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Represents the details of a delivery status.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeliveryStatusDetails {
+    /// A description of the delivery status.
+    #[serde(rename = "statusMessage")]
+    pub status_message: Option<String>,
+}
+
+/// Rate limit hints sent with a throttled response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// The time to wait before retrying, from the `Retry-After` or `retry-after-ms` header.
+    pub retry_after: Option<std::time::Duration>,
+
+    /// The remaining request quota, from an `x-ms-ratelimit-remaining-*` header.
+    pub remaining: Option<u64>,
+
+    /// When the quota resets, from an `x-ms-ratelimit-reset` header, as sent by the server.
+    pub reset: Option<String>,
+}
+
+/// A well-known ACS error `code`, parsed from an error response.
+///
+/// Marked `#[non_exhaustive]` because more codes may get their own variant; codes without one
+/// are kept as `Other`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AcsErrorCode {
+    /// The sender's domain is not linked to the Communication Services resource.
+    DomainNotLinked,
+
+    /// The sender address is not a valid address of a linked domain.
+    InvalidSenderAddress,
+
+    /// The sender's domain is not verified or can't send email.
+    InvalidSenderDomain,
+
+    /// The request was not authenticated, e.g. a wrong access key or an expired token.
+    Unauthorized,
+
+    /// Any other code, as returned by ACS.
+    Other(String),
+}
+
+impl AcsErrorCode {
+    /// Parses an ACS error code, ignoring case.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The `code` field of an ACS error response.
+    ///
+    /// # Returns
+    ///
+    /// * `AcsErrorCode` - The matching variant, or `Other` holding the code as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use azure_email_service::domain::entities::models::AcsErrorCode;
+    ///
+    /// assert_eq!(AcsErrorCode::from_code("DomainNotLinked"), AcsErrorCode::DomainNotLinked);
+    /// assert_eq!(
+    ///     AcsErrorCode::from_code("InvalidSenderAddress"),
+    ///     AcsErrorCode::InvalidSenderAddress
+    /// );
+    /// assert_eq!(
+    ///     AcsErrorCode::from_code("InvalidSenderDomain"),
+    ///     AcsErrorCode::InvalidSenderDomain
+    /// );
+    /// assert_eq!(AcsErrorCode::from_code("unauthorized"), AcsErrorCode::Unauthorized);
+    /// assert_eq!(
+    ///     AcsErrorCode::from_code("TooManyRecipients"),
+    ///     AcsErrorCode::Other("TooManyRecipients".to_string())
+    /// );
+    /// ```
+    pub fn from_code(code: &str) -> Self {
+        [
+            AcsErrorCode::DomainNotLinked,
+            AcsErrorCode::InvalidSenderAddress,
+            AcsErrorCode::InvalidSenderDomain,
+            AcsErrorCode::Unauthorized,
+        ]
+        .into_iter()
+        .find(|known| known.as_str().eq_ignore_ascii_case(code))
+        .unwrap_or_else(|| AcsErrorCode::Other(code.to_string()))
+    }
+
+    /// Returns the code as sent by ACS.
+    pub fn as_str(&self) -> &str {
+        match self {
+            AcsErrorCode::DomainNotLinked => "DomainNotLinked",
+            AcsErrorCode::InvalidSenderAddress => "InvalidSenderAddress",
+            AcsErrorCode::InvalidSenderDomain => "InvalidSenderDomain",
+            AcsErrorCode::Unauthorized => "Unauthorized",
+            AcsErrorCode::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for AcsErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
-/// Represents the content of an email.
-#[derive(Serialize, Deserialize, Debug)]
-pub struct EmailContent {
-    /// The subject of the email.
-    #[serde(rename = "subject")]
-    pub subject: Option<String>,
+/// A problem found while building or validating a `SentEmail`.
+///
+/// Marked `#[non_exhaustive]` because more validation checks may be added.
+///
+/// There is no missing sender variant: `SentEmailBuilder::build` defaults an unset sender to an
+/// empty one, which `ACSClient` fills in with `ACSClientBuilder::default_sender` when sending,
+/// or rejects then if no default sender is set. A malformed sender is an `InvalidAddress`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// No content was set on the builder.
+    MissingContent,
 
-    /// The plain text content of the email.
-    #[serde(rename = "plainText")]
-    pub plain_text: Option<String>,
+    /// The subject is missing or empty.
+    MissingSubject,
 
-    /// The HTML content of the email.
-    #[serde(rename = "html")]
-    pub html: Option<String>,
-}
+    /// Neither a plain text nor an HTML body is set.
+    MissingBody,
 
-/// Represents a header in an email.
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Header {
-    /// The name of the header.
-    #[serde(rename = "name")]
-    name: Option<String>,
+    /// No recipients were set on the builder.
+    MissingRecipients,
 
-    /// The value of the header.
-    #[serde(rename = "value")]
-    value: Option<String>,
-}
+    /// The recipients were set, but the to, cc and bcc lists are all empty.
+    NoRecipients,
 
-/// Represents the recipients of an email.
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Recipients {
-    /// The primary recipients of the email.
-    #[serde(rename = "to")]
-    pub to: Option<Vec<EmailAddress>>,
+    /// A sender, recipient or reply-to address is malformed, holding the address.
+    InvalidAddress(String),
 
-    /// The CC recipients of the email.
-    #[serde(rename = "cc")]
-    pub cc: Option<Vec<EmailAddress>>,
+    /// A custom header is malformed or not allowed by ACS, holding the header name.
+    InvalidHeader(String),
 
-    /// The BCC recipients of the email.
-    #[serde(rename = "bcc")]
-    pub b_cc: Option<Vec<EmailAddress>>,
-}
+    /// An attachment has an invalid content type or unreadable content.
+    InvalidAttachment {
+        /// The name of the attachment.
+        name: String,
 
-/// Represents an email address.
-#[derive(Serialize, Deserialize, Debug)]
-pub struct EmailAddress {
-    /// The email address.
-    #[serde(rename = "address")]
-    pub email: Option<String>,
+        /// What is wrong with it.
+        reason: String,
+    },
 
-    /// The display name associated with the email address.
-    #[serde(rename = "displayName")]
-    pub display_name: Option<String>,
+    /// The attachments exceed the total size limit, starting with this attachment.
+    AttachmentTooLarge {
+        /// The name of the attachment that crossed the limit.
+        name: String,
+
+        /// Its decoded size in bytes.
+        size: usize,
+    },
+
+    /// The HTML body is larger than the builder's limit, with `strict_size` enabled.
+    HtmlTooLarge {
+        /// The size of the HTML body in bytes.
+        size: usize,
+
+        /// The limit in bytes.
+        max_size: usize,
+    },
 }
 
-/// Represents an error response.
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ErrorResponse {
-    /// The error details.
-    #[serde(rename = "error")]
-    pub error: Option<ErrorDetail>,
+/// Represents an error returned by the ACS email client.
+///
+/// Features can add variants, e.g. `Cancelled` with `cancellation`, so matches need a `_` arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AcsError {
+    /// An error response returned by the ACS REST API.
+    Api {
+        /// The HTTP status code of the response.
+        http_status: Option<u16>,
+
+        /// The parsed `code` of the error details, if any. The raw code stays in `response`.
+        code: Option<AcsErrorCode>,
+
+        /// The error details returned in the response body, boxed to keep `AcsError` small.
+        response: Box<ErrorResponse>,
+    },
+
+    /// A `429 Too Many Requests` response, returned once retries are exhausted or disabled.
+    TooManyRequests {
+        /// The rate limit hints sent with the response.
+        rate_limit: Box<RateLimitInfo>,
+
+        /// The error details returned in the response body, boxed to keep `AcsError` small.
+        response: Box<ErrorResponse>,
+    },
+
+    /// An error raised by the client itself, e.g. a serialization failure.
+    Client(String),
+
+    /// The connection to the server failed, e.g. a DNS, TLS or refused connection error.
+    Connect(String),
+
+    /// The HTTP request timed out.
+    RequestTimeout(String),
+
+    /// The response body could not be read or decoded.
+    Decode(String),
+
+    /// The response had no body, e.g. `204 No Content` while a status is not available yet.
+    EmptyResponse {
+        /// The HTTP status code of the response.
+        status: u16,
+    },
+
+    /// An operation did not complete within the given duration.
+    Timeout(std::time::Duration),
+
+    /// The email operation finished with a `Failed`, `Canceled` or `Unknown` status.
+    Unsuccessful(EmailSendStatusType),
+
+    /// The email operation has not finished yet, with a `NotStarted` or `Running` status.
+    NotTerminal(EmailSendStatusType),
+
+    /// Status polling was interrupted by the client's cancellation token.
+    #[cfg(feature = "cancellation")]
+    Cancelled,
 }
 
 /// Represents the parameters of an endpoint.
@@ -383,9 +2491,850 @@ impl FromStr for EmailSendStatusType {
     }
 }
 
+impl fmt::Display for EmailAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let email = self.email.as_deref().unwrap_or_default();
+        match self.display_name.as_deref() {
+            Some(name) if !name.is_empty() => {
+                // Quote the display name when it contains RFC 5322 special characters
+                if name.chars().any(|c| "()<>[]:;@\\,.\"".contains(c)) {
+                    let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+                    write!(f, "\"{}\" <{}>", escaped, email)
+                } else {
+                    write!(f, "{} <{}>", name, email)
+                }
+            }
+            _ => write!(f, "{}", email),
+        }
+    }
+}
+
+impl FromStr for EmailAddress {
+    type Err = String;
+
+    /// Parses an address in either `Jane Doe <jane@example.com>` or bare `jane@example.com` form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (display_name, email) = match (s.rfind('<'), s.strip_suffix('>')) {
+            (Some(start), Some(rest)) => {
+                let name = s[..start].trim();
+                let name = match name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+                    Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+                    None => name.to_string(),
+                };
+                let name = if name.is_empty() { None } else { Some(name) };
+                (name, rest[start + 1..].trim())
+            }
+            _ => (None, s),
+        };
+
+        if !is_valid_address(email) {
+            return Err(format!("Invalid email address: {}", s));
+        }
+
+        Ok(EmailAddress {
+            email: Some(email.to_string()),
+            display_name,
+        })
+    }
+}
+
 impl fmt::Display for EmailSendStatus {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0).expect("EmailSendStatus: panic message");
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for ErrorDetail {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message.as_deref().unwrap_or("Unknown error"))?;
+        if let Some(code) = &self.code {
+            write!(f, " (code: {})", code)?;
+        }
+        if let Some(target) = &self.target {
+            write!(f, " (target: {})", target)?;
+        }
+        if let Some(additional_info) = &self.additional_info {
+            for info in additional_info {
+                write!(f, "\n  * {}", info)?;
+            }
+        }
+        if let Some(details) = &self.details {
+            for detail in details {
+                write!(f, "\n  - {}", detail)?;
+            }
+        }
         Ok(())
     }
 }
+
+impl fmt::Display for ErrorAdditionalInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.info_type.as_deref().unwrap_or("Unknown"))?;
+        match &self.info {
+            Some(serde_json::Value::String(info)) => write!(f, "{}", info),
+            Some(info) => write!(f, "{}", info),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ErrorResponse {
+    // The additional information entries of the error, empty if there are none
+    fn additional_info(&self) -> &[ErrorAdditionalInfo] {
+        self.error
+            .as_ref()
+            .and_then(|error| error.additional_info.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(error) => write!(f, "{}", error),
+            None => write!(f, "Unknown error"),
+        }
+    }
+}
+
+impl fmt::Display for AcsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AcsError::Api {
+                http_status: Some(http_status),
+                response,
+                ..
+            } => write!(f, "ACS API error (HTTP {}): {}", http_status, response),
+            AcsError::Api { response, .. } => write!(f, "ACS API error: {}", response),
+            AcsError::TooManyRequests {
+                rate_limit,
+                response,
+            } => {
+                write!(f, "ACS API error (HTTP 429): {}", response)?;
+                if let Some(remaining) = rate_limit.remaining {
+                    write!(f, " (remaining quota: {})", remaining)?;
+                }
+                Ok(())
+            }
+            AcsError::Client(message) => write!(f, "{}", message),
+            AcsError::Connect(message) => write!(f, "Connection failed: {}", message),
+            AcsError::RequestTimeout(message) => write!(f, "Request timed out: {}", message),
+            AcsError::Decode(message) => write!(f, "Failed to decode response: {}", message),
+            AcsError::EmptyResponse { status } => {
+                write!(f, "ACS returned an empty response (HTTP {})", status)
+            }
+            AcsError::Timeout(duration) => write!(f, "Timed out after {:?}", duration),
+            AcsError::Unsuccessful(status) => {
+                write!(f, "Email operation finished with status {}", status)
+            }
+            AcsError::NotTerminal(status) => {
+                write!(f, "Email operation has not finished, status {}", status)
+            }
+            #[cfg(feature = "cancellation")]
+            AcsError::Cancelled => write!(f, "Status polling was cancelled"),
+        }
+    }
+}
+
+impl AcsError {
+    /// Creates an `Api` error, parsing the code of its error details.
+    ///
+    /// # Arguments
+    ///
+    /// * `http_status` - The HTTP status code of the response, if known.
+    /// * `response` - The error details returned in the response body.
+    ///
+    /// # Returns
+    ///
+    /// * `AcsError` - The `Api` error.
+    pub fn api(http_status: Option<u16>, response: ErrorResponse) -> Self {
+        let code = response
+            .error
+            .as_ref()
+            .and_then(|error| error.code.as_deref())
+            .map(AcsErrorCode::from_code);
+        AcsError::Api {
+            http_status,
+            code,
+            response: Box::new(response),
+        }
+    }
+
+    /// Returns the additional information entries reported by the ACS API.
+    ///
+    /// # Returns
+    ///
+    /// * `&[ErrorAdditionalInfo]` - The `type`/`info` pairs, or an empty slice if there are none.
+    pub fn additional_info(&self) -> &[ErrorAdditionalInfo] {
+        match self {
+            AcsError::Api { response, .. } => response.additional_info(),
+            AcsError::TooManyRequests { response, .. } => response.additional_info(),
+            _ => &[],
+        }
+    }
+
+    /// Returns the HTTP status code of the response that caused the error, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u16>` - The HTTP status code, or `None` for errors raised by the client itself.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            AcsError::Api { http_status, .. } => *http_status,
+            AcsError::TooManyRequests { .. } => Some(429),
+            AcsError::EmptyResponse { status } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns whether retrying the request later might succeed.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True for `429 Too Many Requests`, `5xx` server errors, connection failures
+    ///   and request timeouts.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, AcsError::Connect(_) | AcsError::RequestTimeout(_))
+            || self
+                .http_status()
+                .is_some_and(|status| status == 429 || (500..600).contains(&status))
+    }
+}
+
+impl std::error::Error for AcsError {}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MissingContent => write!(f, "Content is required"),
+            BuildError::MissingSubject => write!(f, "Subject is required"),
+            BuildError::MissingBody => {
+                write!(f, "Body is required, set plain text or HTML content")
+            }
+            BuildError::MissingRecipients => write!(f, "Recipients are required"),
+            BuildError::NoRecipients => write!(f, "At least one recipient is required"),
+            BuildError::InvalidAddress(address) => write!(f, "Invalid address: '{}'", address),
+            BuildError::InvalidHeader(name) => {
+                write!(f, "Header '{}' is not allowed by ACS", name)
+            }
+            BuildError::InvalidAttachment { name, reason } => {
+                write!(f, "Invalid attachment '{}': {}", name, reason)
+            }
+            BuildError::AttachmentTooLarge { name, size } => write!(
+                f,
+                "Attachment '{}' ({} bytes) exceeds the total attachment size limit",
+                name, size
+            ),
+            BuildError::HtmlTooLarge { size, max_size } => write!(
+                f,
+                "HTML body is {} bytes, more than {} bytes; consider moving inline images to attachments referenced by cid",
+                size, max_size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masked_keeps_the_first_character_and_the_domain() {
+        let address = EmailAddress::from("jane@contoso.com");
+        assert_eq!(address.masked().email.as_deref(), Some("j***@contoso.com"));
+    }
+
+    #[test]
+    fn masked_hides_the_display_name_and_an_address_without_a_domain() {
+        let masked = EmailAddress::from("jane@contoso.com")
+            .with_display_name("Jane Doe")
+            .masked();
+        assert_eq!(masked.display_name.as_deref(), Some("***"));
+
+        let masked = EmailAddress::from("not-an-address").masked();
+        assert_eq!(masked.email.as_deref(), Some("***"));
+    }
+
+    #[test]
+    fn redacted_masks_every_address_and_replaces_attachment_content_by_its_length() {
+        let email = SentEmailBuilder::new()
+            .sender("donotreply@example.com".to_string())
+            .subject("Subject")
+            .plain_text("Body")
+            .to("jane@contoso.com")
+            .cc("john@contoso.com")
+            .bcc("ann@contoso.com")
+            .reply_to_one("support@contoso.com".into())
+            .attachments(vec![EmailAttachment::from_bytes(
+                "hello.txt",
+                "text/plain",
+                b"hello",
+            )])
+            .build()
+            .unwrap();
+        let redacted = email.redacted();
+
+        let first = |list: &Option<Vec<EmailAddress>>| list.as_ref().unwrap()[0].email.clone();
+        assert_eq!(
+            first(&redacted.recipients.to).as_deref(),
+            Some("j***@contoso.com")
+        );
+        assert_eq!(
+            first(&redacted.recipients.cc).as_deref(),
+            Some("j***@contoso.com")
+        );
+        assert_eq!(
+            first(&redacted.recipients.b_cc).as_deref(),
+            Some("a***@contoso.com")
+        );
+        assert_eq!(
+            first(&redacted.reply_to).as_deref(),
+            Some("s***@contoso.com")
+        );
+        let attachment = &redacted.attachments.as_ref().unwrap()[0];
+        assert_eq!(attachment.name(), Some("hello.txt"));
+        assert_eq!(
+            attachment.content_bytes_base64(),
+            Some("<8 base64 characters>")
+        );
+        // The sender and content aren't personal data of the recipients
+        assert_eq!(redacted.sender, email.sender);
+        assert_eq!(redacted.content, email.content);
+    }
+
+    #[test]
+    fn error_response_exposes_additional_info() {
+        let body = r#"{
+            "error": {
+                "code": "TooManyRequests",
+                "message": "The request was throttled.",
+                "additionalInfo": [
+                    { "type": "QuotaExceeded", "info": { "retryAfterSeconds": 60 } }
+                ]
+            }
+        }"#;
+        let error = AcsError::api(Some(429), serde_json::from_str(body).unwrap());
+        let additional_info = error.additional_info();
+        assert_eq!(additional_info.len(), 1);
+        assert_eq!(
+            additional_info[0].info_type.as_deref(),
+            Some("QuotaExceeded")
+        );
+        assert_eq!(
+            additional_info[0].info,
+            Some(serde_json::json!({ "retryAfterSeconds": 60 }))
+        );
+        assert!(error
+            .to_string()
+            .contains("QuotaExceeded: {\"retryAfterSeconds\":60}"));
+    }
+
+    #[test]
+    fn email_send_status_displays_every_status() {
+        let cases = [
+            (EmailSendStatusType::Unknown, "Unknown"),
+            (EmailSendStatusType::Canceled, "Canceled"),
+            (EmailSendStatusType::Failed, "Failed"),
+            (EmailSendStatusType::NotStarted, "NotStarted"),
+            (EmailSendStatusType::Running, "Running"),
+            (EmailSendStatusType::Succeeded, "Succeeded"),
+        ];
+        for (status, expected) in cases {
+            assert_eq!(EmailSendStatus(status).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn sent_email_response_deserializes_the_status_acs_sends() {
+        let body = r#"{ "id": "4d3f0a1c-0000-0000-0000-000000000000", "status": "NotStarted" }"#;
+        let response: SentEmailResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(
+            response.status.unwrap().to_type(),
+            EmailSendStatusType::NotStarted
+        );
+    }
+
+    #[test]
+    fn email_send_status_deserializes_a_bare_string_or_a_status_object() {
+        let bare: EmailSendStatus = serde_json::from_str(r#""Running""#).unwrap();
+        assert_eq!(bare.to_type(), EmailSendStatusType::Running);
+        let object: EmailSendStatus = serde_json::from_str(r#"{ "status": "Succeeded" }"#).unwrap();
+        assert_eq!(object.to_type(), EmailSendStatusType::Succeeded);
+        let unknown: EmailSendStatus = serde_json::from_str(r#""Paused""#).unwrap();
+        assert_eq!(unknown.to_type(), EmailSendStatusType::Unknown);
+    }
+
+    #[test]
+    fn email_send_status_type_round_trips_through_its_name() {
+        for status in [
+            EmailSendStatusType::Canceled,
+            EmailSendStatusType::Failed,
+            EmailSendStatusType::NotStarted,
+            EmailSendStatusType::Running,
+            EmailSendStatusType::Succeeded,
+        ] {
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(json, format!("\"{}\"", status));
+            assert_eq!(
+                EmailSendStatusType::from_str(&status.to_string()),
+                Ok(status)
+            );
+        }
+    }
+
+    #[test]
+    fn calendar_attachment_encodes_the_invite() {
+        let ics = "BEGIN:VCALENDAR\r\nMETHOD:REQUEST\r\nEND:VCALENDAR\r\n";
+        let attachment = EmailAttachment::calendar("invite.ics", ics);
+        assert_eq!(
+            attachment.attachment_type.as_deref(),
+            Some("text/calendar; method=REQUEST")
+        );
+        let decoded = general_purpose::STANDARD
+            .decode(attachment.content_bytes_base64.unwrap())
+            .unwrap();
+        assert_eq!(decoded, ics.as_bytes());
+    }
+
+    fn email_builder() -> SentEmailBuilder {
+        SentEmailBuilder::new()
+            .sender("donotreply@example.com".to_string())
+            .subject("Subject")
+            .plain_text("Body")
+            .to("jane@example.com")
+    }
+
+    #[test]
+    fn reply_to_and_headers_are_only_serialized_when_set() {
+        let json = serde_json::to_value(email_builder().build().unwrap()).unwrap();
+        assert!(json.get("replyTo").is_none());
+        assert!(json.get("headers").is_none());
+
+        let email = email_builder()
+            .reply_to_one("support@example.com".into())
+            .header("X-Campaign", "spring")
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(email).unwrap();
+        assert_eq!(
+            json["replyTo"],
+            serde_json::json!([{ "address": "support@example.com", "displayName": null }])
+        );
+        assert_eq!(
+            json["headers"],
+            serde_json::json!([{ "name": "X-Campaign", "value": "spring" }])
+        );
+    }
+
+    #[test]
+    fn reply_to_all_serializes_every_reply_to_address() {
+        let email = email_builder()
+            .reply_to_all(["support@example.com", "sales@example.com"])
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(email).unwrap();
+        assert_eq!(
+            json["replyTo"],
+            serde_json::json!([
+                { "address": "support@example.com", "displayName": null },
+                { "address": "sales@example.com", "displayName": null }
+            ])
+        );
+    }
+
+    #[test]
+    fn build_rejects_an_invalid_reply_to_address() {
+        let result = email_builder().reply_to_all(["not-an-address"]).build();
+        assert_eq!(
+            result.unwrap_err(),
+            BuildError::InvalidAddress("not-an-address".to_string())
+        );
+    }
+
+    fn addresses(list: &Option<Vec<EmailAddress>>) -> Option<Vec<&str>> {
+        list.as_ref().map(|list| {
+            list.iter()
+                .map(|address| address.email.as_deref().unwrap())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn dedupe_recipients_removes_duplicates_within_a_list() {
+        let email = email_builder()
+            .to("Jane@Example.com")
+            .to("john@example.com")
+            .dedupe_recipients()
+            .build()
+            .unwrap();
+        assert_eq!(
+            addresses(&email.recipients.to),
+            Some(vec!["jane@example.com", "john@example.com"])
+        );
+    }
+
+    #[test]
+    fn dedupe_recipients_keeps_the_first_list_an_address_is_in() {
+        let email = email_builder()
+            .cc("jane@example.com")
+            .bcc("JANE@example.com")
+            .bcc("audit@example.com")
+            .dedupe_recipients()
+            .build()
+            .unwrap();
+        assert_eq!(
+            addresses(&email.recipients.to),
+            Some(vec!["jane@example.com"])
+        );
+        assert_eq!(addresses(&email.recipients.cc), None);
+        assert_eq!(
+            addresses(&email.recipients.b_cc),
+            Some(vec!["audit@example.com"])
+        );
+    }
+
+    #[test]
+    fn build_keeps_duplicate_recipients_by_default() {
+        let email = email_builder().to("jane@example.com").build().unwrap();
+        assert_eq!(
+            addresses(&email.recipients.to),
+            Some(vec!["jane@example.com", "jane@example.com"])
+        );
+    }
+
+    #[test]
+    fn build_requires_at_least_one_recipient() {
+        let recipients = Recipients {
+            to: Some(vec![]),
+            cc: None,
+            b_cc: None,
+        };
+        let result = SentEmailBuilder::new()
+            .sender("donotreply@example.com".to_string())
+            .subject("Subject")
+            .plain_text("Body")
+            .recipients(recipients)
+            .build();
+        assert_eq!(result.unwrap_err(), BuildError::NoRecipients);
+    }
+
+    #[test]
+    fn build_accepts_a_bcc_only_email() {
+        let email = SentEmailBuilder::new()
+            .sender("donotreply@example.com".to_string())
+            .subject("Subject")
+            .plain_text("Body")
+            .bcc("jane@example.com")
+            .build()
+            .unwrap();
+        assert_eq!(email.recipients.count(), 1);
+        assert!(email.recipients.to.is_none());
+    }
+
+    #[test]
+    fn tracking_is_only_serialized_when_set() {
+        let json = serde_json::to_value(email_builder().build().unwrap()).unwrap();
+        assert!(json.get("userEngagementTrackingDisabled").is_none());
+
+        let json =
+            serde_json::to_value(email_builder().disable_tracking().build().unwrap()).unwrap();
+        assert_eq!(json["userEngagementTrackingDisabled"], true);
+
+        let json =
+            serde_json::to_value(email_builder().enable_tracking().build().unwrap()).unwrap();
+        assert_eq!(json["userEngagementTrackingDisabled"], false);
+    }
+
+    #[test]
+    fn build_rejects_an_attachment_that_is_not_base64() {
+        let attachment = EmailAttachment {
+            name: Some("report.pdf".to_string()),
+            attachment_type: Some("application/pdf".to_string()),
+            content_bytes_base64: Some("not base64!".to_string()),
+            source: None,
+        };
+        let error = email_builder()
+            .attachments(vec![attachment])
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                BuildError::InvalidAttachment { name, reason }
+                    if name == "report.pdf" && reason.starts_with("not valid base64")
+            ),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn queued_email_round_trips_through_json() {
+        let queued = QueuedEmail::new(email_builder().build().unwrap())
+            .scheduled_at(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000));
+        let json = serde_json::to_string(&queued).unwrap();
+        let restored: QueuedEmail = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, queued);
+
+        // A queued email without a schedule omits the field and restores it as `None`
+        let queued = QueuedEmail::new(email_builder().build().unwrap());
+        let json = serde_json::to_value(&queued).unwrap();
+        assert!(json.get("scheduledAt").is_none());
+        let restored: QueuedEmail = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, queued);
+    }
+
+    fn values() -> HashMap<String, String> {
+        HashMap::from([
+            ("name".to_string(), "Jane".to_string()),
+            ("code".to_string(), "{{name}}".to_string()),
+        ])
+    }
+
+    #[test]
+    fn substitute_placeholders_replaces_a_matched_placeholder() {
+        let text = substitute_placeholders("Hi {{name}}, {{ name }}!", &values(), true).unwrap();
+        assert_eq!(text, "Hi Jane, Jane!");
+    }
+
+    #[test]
+    fn substitute_placeholders_does_not_expand_placeholders_in_values() {
+        let text = substitute_placeholders("Code {{code}}", &values(), true).unwrap();
+        assert_eq!(text, "Code {{name}}");
+    }
+
+    #[test]
+    fn substitute_placeholders_keeps_an_unmatched_placeholder_unless_strict() {
+        let text = substitute_placeholders("Hi {{name}} {{unknown}}", &values(), false).unwrap();
+        assert_eq!(text, "Hi Jane {{unknown}}");
+
+        let error =
+            substitute_placeholders("Hi {{name}} {{unknown}}", &values(), true).unwrap_err();
+        assert_eq!(error, "No value for placeholder '{{unknown}}'");
+    }
+
+    #[test]
+    fn personalize_substitutes_the_subject_and_both_bodies() {
+        let template = EmailContent {
+            subject: Some("For {{name}}".to_string()),
+            plain_text: Some("Hi {{name}}".to_string()),
+            html: Some("<p>{{name}}</p>".to_string()),
+        };
+        let content = template.personalize(&values(), true).unwrap();
+        assert_eq!(content.subject.as_deref(), Some("For Jane"));
+        assert_eq!(content.plain_text.as_deref(), Some("Hi Jane"));
+        assert_eq!(content.html.as_deref(), Some("<p>Jane</p>"));
+    }
+
+    #[test]
+    fn validate_attachments_accepts_a_content_type_with_parameters() {
+        let attachment = EmailAttachment::from_bytes("a.txt", "text/plain; charset=utf-8", b"a");
+        assert_eq!(validate_attachments(&[attachment], 10), Ok(()));
+    }
+
+    #[test]
+    fn validate_attachments_rejects_malformed_content_types() {
+        for content_type in ["text", "/plain", "a/b/c"] {
+            let attachment = EmailAttachment::from_bytes("a.txt", content_type, b"a");
+            assert_eq!(
+                validate_attachments(&[attachment], 10),
+                Err(BuildError::InvalidAttachment {
+                    name: "a.txt".to_string(),
+                    reason: format!("invalid content type '{}'", content_type),
+                }),
+                "{}",
+                content_type
+            );
+        }
+    }
+
+    #[test]
+    fn validate_attachments_limits_the_sum_of_the_sizes() {
+        let attachments = [
+            EmailAttachment::from_bytes("first.bin", "application/octet-stream", &[0; 6]),
+            EmailAttachment::from_bytes("second.bin", "application/octet-stream", &[0; 5]),
+        ];
+        // Each attachment fits on its own, together they don't
+        assert_eq!(validate_attachments(&attachments, 11), Ok(()));
+        assert_eq!(
+            validate_attachments(&attachments, 10),
+            Err(BuildError::AttachmentTooLarge {
+                name: "second.bin".to_string(),
+                size: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn validate_headers_rejects_a_standard_header_acs_does_not_allow() {
+        let headers = [
+            Header::new("X-Campaign", "spring"),
+            Header::new("Received", "a"),
+        ];
+        assert_eq!(
+            validate_headers(&headers),
+            Err(BuildError::InvalidHeader("Received".to_string()))
+        );
+    }
+
+    #[test]
+    fn validate_headers_accepts_custom_and_allowed_headers_in_any_case() {
+        let headers = [
+            Header::new("x-custom", "1"),
+            Header::new("X-Custom", "1"),
+            Header::new("list-unsubscribe", "<https://example.com/u>"),
+            Header::new("LIST-UNSUBSCRIBE-POST", "List-Unsubscribe=One-Click"),
+            Header::new("importance", "high"),
+        ];
+        assert_eq!(validate_headers(&headers), Ok(()));
+    }
+
+    #[test]
+    fn list_unsubscribe_adds_both_one_click_headers() {
+        let email = email_builder()
+            .list_unsubscribe("https://example.com/unsubscribe?id=1")
+            .build()
+            .unwrap();
+        let headers: Vec<(&str, &str)> = email
+            .headers
+            .iter()
+            .flatten()
+            .map(|header| (header.name().unwrap(), header.value().unwrap()))
+            .collect();
+        assert_eq!(
+            headers,
+            [
+                ("List-Unsubscribe", "<https://example.com/unsubscribe?id=1>"),
+                ("List-Unsubscribe-Post", "List-Unsubscribe=One-Click"),
+            ]
+        );
+    }
+
+    fn builder_without_content() -> SentEmailBuilder {
+        SentEmailBuilder::new()
+            .sender("donotreply@example.com".to_string())
+            .to("jane@example.com")
+    }
+
+    #[test]
+    fn build_requires_a_non_empty_subject() {
+        let result = builder_without_content().plain_text("Body").build();
+        assert_eq!(result.unwrap_err(), BuildError::MissingSubject);
+
+        let result = builder_without_content()
+            .subject("")
+            .plain_text("Body")
+            .build();
+        assert_eq!(result.unwrap_err(), BuildError::MissingSubject);
+    }
+
+    #[test]
+    fn build_requires_a_non_empty_body() {
+        let result = builder_without_content().subject("Subject").build();
+        assert_eq!(result.unwrap_err(), BuildError::MissingBody);
+
+        // An empty plain text body is fine as long as there is an HTML body
+        let email = builder_without_content()
+            .subject("Subject")
+            .plain_text("")
+            .html("<p>Body</p>")
+            .build()
+            .unwrap();
+        assert_eq!(email.content.html.as_deref(), Some("<p>Body</p>"));
+    }
+
+    #[test]
+    fn from_path_with_limit_rejects_a_file_over_the_encoded_limit() {
+        let dir =
+            std::env::temp_dir().join(format!("azure_email_service_limit_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("report.bin");
+        std::fs::write(&path, b"0123456789").unwrap();
+
+        let rejected = EmailAttachment::from_path_with_limit(&path, 15);
+        let accepted = EmailAttachment::from_path_with_limit(&path, 16);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let error = rejected.unwrap_err();
+        assert!(
+            error.contains("would be 16 bytes once base64 encoded, over the 15 byte limit"),
+            "{}",
+            error
+        );
+        let attachment = accepted.unwrap();
+        let json = serde_json::to_value(&attachment).unwrap();
+        assert_eq!(json["name"], "report.bin");
+        assert_eq!(json["contentInBase64"], "MDEyMzQ1Njc4OQ==");
+    }
+
+    #[test]
+    fn oversized_html_builds_unless_strict_size_is_set() {
+        let html = "x".repeat(DEFAULT_MAX_HTML_SIZE + 1);
+        assert!(email_builder().html(&html).build().is_ok());
+
+        assert_eq!(
+            email_builder().html(&html).strict_size().build().err(),
+            Some(BuildError::HtmlTooLarge {
+                size: DEFAULT_MAX_HTML_SIZE + 1,
+                max_size: DEFAULT_MAX_HTML_SIZE,
+            })
+        );
+        assert_eq!(
+            email_builder()
+                .html("<p>Hello</p>")
+                .max_html_size(5)
+                .strict_size()
+                .build()
+                .err(),
+            Some(BuildError::HtmlTooLarge {
+                size: 12,
+                max_size: 5
+            })
+        );
+    }
+
+    #[test]
+    fn validate_structure_checks_the_sender_and_every_address_list() {
+        let invalid = || Some(vec![EmailAddress::from("not-an-address")]);
+        let email = || email_builder().build().unwrap();
+        let mut to = email();
+        to.recipients.to = invalid();
+        let mut cc = email();
+        cc.recipients.cc = invalid();
+        let mut bcc = email();
+        bcc.recipients.b_cc = invalid();
+        let mut reply_to = email();
+        reply_to.reply_to = invalid();
+        let mut sender = email();
+        sender.sender = "not-an-address".to_string();
+
+        for email in [to, cc, bcc, reply_to, sender] {
+            assert_eq!(
+                email.validate_structure(),
+                Err(BuildError::InvalidAddress("not-an-address".to_string()))
+            );
+        }
+
+        let mut email = email_builder().build().unwrap();
+        email.sender = String::new();
+        assert_eq!(email.validate_structure(), Ok(()));
+    }
+
+    #[test]
+    fn status_types_classify_terminal_and_successful_statuses() {
+        for (status, terminal, success) in [
+            (EmailSendStatusType::Unknown, true, false),
+            (EmailSendStatusType::Canceled, true, false),
+            (EmailSendStatusType::Failed, true, false),
+            (EmailSendStatusType::NotStarted, false, false),
+            (EmailSendStatusType::Running, false, false),
+            (EmailSendStatusType::Succeeded, true, true),
+        ] {
+            assert_eq!(status.is_terminal(), terminal, "{:?}", status);
+            assert_eq!(status.is_success(), success, "{:?}", status);
+        }
+    }
+}
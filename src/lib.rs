@@ -0,0 +1,16 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+//! Azure Communication Services (ACS) email client.
+//!
+//! The CLI in `main.rs` is built only with the `cli` feature (enabled by default). Library
+//! consumers can depend on this crate with `default-features = false` to skip `clap`,
+//! `lettre`, `dotenv` and `pretty_env_logger`.
+//!
+//! On `wasm32-unknown-unknown` (built with `default-features = false`), `ACSClient` supports
+//! `send_email`, `send_email_with_id`, `build_send_request` and `get_email_status`. The
+//! callback and status polling APIs spawn tokio tasks and are only available on native targets.
+
+pub mod adapters;
+pub mod domain;
@@ -1,16 +1,20 @@
-mod adapters;
-mod domain;
-
 use log::{debug, error, info};
-use std::{env, time};
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
-use crate::adapters::gateways::acs_email::ACSClientBuilder;
-use crate::domain::entities::models::{
-    EmailAddress, EmailContent, EmailSendStatusType, Recipients, SentEmailBuilder,
+use azure_email_service::adapters::gateways::acs_email::{ACSClient, ACSClientBuilder};
+use azure_email_service::domain::entities::models::{
+    EmailAddress, EmailAttachment, EmailContent, EmailSendStatusType, SentEmail, SentEmailBuilder,
 };
-use clap::{Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use lettre::address::Envelope;
 use lettre::message::header::ContentType;
-use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
 use lettre::{Message, SmtpTransport, Transport};
 
 /// Enum representing the authentication methods for the CLI.
@@ -28,6 +32,13 @@ pub enum CLIACSProtocol {
     SMTP,
 }
 
+/// Enum representing the SMTP authentication mechanisms for the CLI.
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CLISmtpAuth {
+    Basic,
+    Xoauth2,
+}
+
 /// Struct representing the command line interface (CLI) arguments.
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -39,50 +50,242 @@ struct Cli {
     /// The authentication method to use.
     #[arg(value_enum, short, long, default_value = "shared-key")]
     auth_method: CLIAuthenticationMethod,
+
+    /// The SMTP authentication mechanism, `xoauth2` uses a token from the --auth-method credentials.
+    #[arg(value_enum, long, default_value = "basic")]
+    smtp_auth: CLISmtpAuth,
+
+    /// Print the final operation ID and status as a JSON line to stdout (REST only).
+    #[arg(long)]
+    json: bool,
+
+    /// The command to run, `send` when omitted.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Enum representing the CLI subcommands.
+#[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
+enum Command {
+    /// Send an email.
+    Send(SendArgs),
+
+    /// Query the status of a previously sent email (REST only).
+    Status(StatusArgs),
+}
+
+/// Struct representing the arguments of the `send` command.
+#[derive(Parser)]
+struct SendArgs {
+    /// Print the operation ID and exit without waiting for a terminal status (REST only).
+    #[arg(long)]
+    no_wait: bool,
+
+    #[command(flatten)]
+    poll: PollArgs,
+
+    /// The subject of the email (REST only).
+    #[arg(long)]
+    subject: Option<String>,
+
+    /// The plain text body of the email (REST only).
+    #[arg(long, conflicts_with = "text_file")]
+    body: Option<String>,
+
+    /// A file containing the plain text body, `-` for stdin (REST only).
+    #[arg(long)]
+    text_file: Option<PathBuf>,
+
+    /// The HTML body of the email (REST only).
+    #[arg(long, conflicts_with = "html_file")]
+    html: Option<String>,
+
+    /// A file containing the HTML body, `-` for stdin (REST only).
+    #[arg(long)]
+    html_file: Option<PathBuf>,
+
+    /// A primary recipient, as `jane@example.com` or `Jane Doe <jane@example.com>` (REST only, repeatable).
+    #[arg(long)]
+    to: Vec<EmailAddress>,
+
+    /// A CC recipient (REST only, repeatable).
+    #[arg(long)]
+    cc: Vec<EmailAddress>,
+
+    /// A BCC recipient (REST only, repeatable).
+    #[arg(long)]
+    bcc: Vec<EmailAddress>,
+
+    /// A file to attach (REST only, repeatable).
+    #[arg(long)]
+    attach: Vec<PathBuf>,
+
+    /// A file containing a complete MIME message to send as is, `-` for stdin (SMTP only).
+    #[arg(
+        long,
+        conflicts_with_all = ["subject", "body", "text_file", "html", "html_file", "attach"]
+    )]
+    raw_mime: Option<PathBuf>,
+}
+
+/// Struct representing the arguments of the `status` command.
+#[derive(Args)]
+struct StatusArgs {
+    /// The operation ID returned when the email was sent.
+    operation_id: String,
+
+    /// Poll until the operation reaches a terminal status instead of printing the current one.
+    #[arg(long)]
+    watch: bool,
+
+    #[command(flatten)]
+    poll: PollArgs,
+}
+
+/// Struct representing the status polling arguments.
+#[derive(Args)]
+struct PollArgs {
+    /// The number of seconds between status polls (REST only).
+    #[arg(long, default_value_t = 5)]
+    poll_interval: u64,
+
+    /// The maximum number of seconds to wait for a terminal status before exiting with an error (REST only).
+    #[arg(long, default_value_t = 600)]
+    timeout: u64,
+}
+
+impl PollArgs {
+    /// Converts the polling arguments into `WaitOptions`, rejecting a poll interval longer than
+    /// the timeout when polling.
+    fn wait_options(&self, wait: bool) -> Result<WaitOptions, String> {
+        if wait && self.poll_interval > self.timeout {
+            return Err(format!(
+                "--poll-interval ({}s) must not be longer than --timeout ({}s)",
+                self.poll_interval, self.timeout
+            ));
+        }
+        Ok(WaitOptions {
+            wait,
+            poll_interval: Duration::from_secs(self.poll_interval),
+            timeout: Duration::from_secs(self.timeout),
+        })
+    }
+}
+
+/// Struct representing how the CLI waits for the final status of a REST send.
+struct WaitOptions {
+    /// Whether to poll the status at all.
+    wait: bool,
+
+    /// The time between status polls.
+    poll_interval: Duration,
+
+    /// The maximum time to wait for a terminal status.
+    timeout: Duration,
+}
+
+/// Struct representing the final outcome of a REST send.
+#[derive(Debug, Default, Serialize)]
+struct SendOutcome {
+    /// The operation ID returned by ACS, if the email was accepted.
+    #[serde(rename = "operationId")]
+    operation_id: Option<String>,
+
+    /// The last status received for the operation.
+    status: Option<EmailSendStatusType>,
+
+    /// The error that ended the send, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl SendOutcome {
+    /// Returns true if the email reached the `Succeeded` status, or, when not waiting,
+    /// if the request itself succeeded.
+    fn is_success(&self, wait: bool) -> bool {
+        if wait {
+            self.status.is_some_and(|status| status.is_success())
+        } else {
+            self.operation_id.is_some() && self.error.is_none()
+        }
+    }
 }
 
 /// Sends an email using SMTP.
 ///
+/// With `raw_mime`, the message is sent as is, so DKIM signatures and custom multipart
+/// structures are preserved. The sender and recipient only form the SMTP envelope.
+///
 /// # Arguments
 ///
 /// * `sender` - The sender's email address.
 /// * `recipient` - The recipient's email address.
 /// * `smtp_server` - The SMTP server address.
-/// * `smtp_user` - The SMTP server username.
-/// * `smtp_password` - The SMTP server password.
+/// * `credentials` - The SMTP username and password, or access token for XOAUTH2.
+/// * `mechanisms` - The SMTP authentication mechanisms to try.
+/// * `raw_mime` - A complete MIME message to send instead of the demo email.
+///
+/// # Returns
+///
+/// * `bool` - True if the email was sent successfully.
 async fn send_email_with_smtp(
     sender: &str,
     recipient: &str,
     smtp_server: &str,
-    smtp_user: &str,
-    smtp_password: &str,
-) {
-    let email = Message::builder()
-        .from(sender.parse().unwrap())
-        .to(recipient.parse().unwrap())
-        .subject("Happy new year")
-        .header(ContentType::TEXT_PLAIN)
-        .body(String::from("Be happy!"))
-        .unwrap();
-
-    debug!("Email: {:#?}", email);
-
-    let creds = Credentials::new(smtp_user.to_owned(), smtp_password.to_owned());
+    credentials: Credentials,
+    mechanisms: Vec<Mechanism>,
+    raw_mime: Option<&[u8]>,
+) -> bool {
     let mailer = SmtpTransport::starttls_relay(smtp_server)
         .unwrap()
-        .credentials(creds)
+        .credentials(credentials)
+        .authentication(mechanisms)
         .build();
 
-    match mailer.send(&email) {
+    let result = match raw_mime {
+        Some(raw_mime) => {
+            let envelope = match Envelope::new(
+                Some(sender.parse().unwrap()),
+                vec![recipient.parse().unwrap()],
+            ) {
+                Ok(envelope) => envelope,
+                Err(e) => {
+                    error!("Invalid SMTP envelope: {e:?}");
+                    return false;
+                }
+            };
+            debug!("Raw MIME message: {} bytes", raw_mime.len());
+            mailer.send_raw(&envelope, raw_mime)
+        }
+        None => {
+            let email = Message::builder()
+                .from(sender.parse().unwrap())
+                .to(recipient.parse().unwrap())
+                .subject("Happy new year")
+                .header(ContentType::TEXT_PLAIN)
+                .body(String::from("Be happy!"))
+                .unwrap();
+
+            debug!("Email: {:#?}", email);
+            mailer.send(&email)
+        }
+    };
+
+    match result {
         Ok(r) => {
             debug!("Email sent: {:#?}", r);
             let messages = r.message();
             for message in messages {
                 debug!("Message: {:#?}", message);
             }
-            info!("Email sent successfully!")
+            info!("Email sent successfully!");
+            true
+        }
+        Err(e) => {
+            error!("Could not send email: {e:?}");
+            false
         }
-        Err(e) => error!("Could not send email: {e:?}"),
     }
 }
 
@@ -91,23 +294,124 @@ async fn send_email_with_smtp(
 /// # Arguments
 ///
 /// * `auth_method` - The authentication method to use.
-/// * `sender` - The sender's email address.
-/// * `recipient` - The recipient's email address.
-/// * `display_name` - The display name for the recipient.
+/// * `email_request` - The email to send.
+/// * `wait_options` - How to wait for the final status.
+///
+/// # Returns
+///
+/// * `SendOutcome` - The operation ID and final status of the send.
 async fn send_email_with_api(
     auth_method: &CLIAuthenticationMethod,
-    sender: &str,
-    recipient: &str,
-    display_name: &str,
+    email_request: &SentEmail,
+    wait_options: &WaitOptions,
+) -> SendOutcome {
+    debug!("Email request: {:#?}", email_request.redacted());
+
+    let acs_client = build_acs_client(auth_method);
+
+    let mut outcome = SendOutcome::default();
+    let resp_send_email = acs_client.send_email(email_request).await;
+    match resp_send_email {
+        Ok(message_resp_id) => {
+            info!("Email was sent with message id: {}", message_resp_id);
+            outcome.operation_id = Some(message_resp_id.clone());
+            if wait_options.wait {
+                wait_for_status(&acs_client, &message_resp_id, wait_options, &mut outcome).await;
+            }
+        }
+        Err(e) => {
+            error!("Error sending email: {}", e);
+            outcome.error = Some(e.to_string());
+        }
+    }
+    outcome
+}
+
+/// Queries the status of a previously sent email using the ACS client.
+///
+/// # Arguments
+///
+/// * `auth_method` - The authentication method to use.
+/// * `operation_id` - The operation ID returned when the email was sent.
+/// * `wait_options` - Whether and how to wait for a terminal status.
+///
+/// # Returns
+///
+/// * `SendOutcome` - The operation ID and current or final status.
+async fn query_email_status(
+    auth_method: &CLIAuthenticationMethod,
+    operation_id: &str,
+    wait_options: &WaitOptions,
+) -> SendOutcome {
+    let acs_client = build_acs_client(auth_method);
+
+    let mut outcome = SendOutcome {
+        operation_id: Some(operation_id.to_owned()),
+        ..Default::default()
+    };
+    if wait_options.wait {
+        wait_for_status(&acs_client, operation_id, wait_options, &mut outcome).await;
+    } else {
+        match acs_client.get_email_status(operation_id).await {
+            Ok(status) => outcome.status = Some(status),
+            Err(e) => {
+                error!("Error getting email status: {}", e);
+                outcome.error = Some(e.to_string());
+            }
+        }
+    }
+    outcome
+}
+
+/// Polls the status of an email until it is terminal, recording the result in the outcome.
+///
+/// # Arguments
+///
+/// * `acs_client` - The ACS client.
+/// * `operation_id` - The operation ID of the email.
+/// * `wait_options` - The polling interval and timeout.
+/// * `outcome` - The outcome to record the final status or error in.
+async fn wait_for_status(
+    acs_client: &ACSClient,
+    operation_id: &str,
+    wait_options: &WaitOptions,
+    outcome: &mut SendOutcome,
 ) {
-    let acs_client_builder: ACSClientBuilder = match auth_method {
+    let resp_status = acs_client
+        .poll_status(
+            operation_id,
+            wait_options.poll_interval,
+            wait_options.timeout,
+        )
+        .await;
+    match resp_status {
+        Ok(status) => {
+            info!("{}\r\n", status);
+            outcome.status = Some(status);
+        }
+        Err(e) => {
+            error!("Error getting email status: {}", e);
+            outcome.error = Some(e.to_string());
+        }
+    }
+}
+
+/// Builds the ACS client for the given authentication method from environment variables.
+///
+/// # Arguments
+///
+/// * `auth_method` - The authentication method to use.
+///
+/// # Returns
+///
+/// * `ACSClient` - The ACS client.
+fn build_acs_client(auth_method: &CLIAuthenticationMethod) -> ACSClient {
+    let acs_client_builder = match auth_method {
         CLIAuthenticationMethod::ManagedIdentity => {
             info!("Using Managed Identity");
             let host_name = get_env_var("ASC_URL");
             debug!("host_name: {}", host_name);
-            ACSClientBuilder::new()
-                .managed_identity()
-                .host(host_name.as_str())
+            ACSClientBuilder::with_managed_identity(host_name.as_str())
         }
         CLIAuthenticationMethod::ServicePrincipal => {
             info!("Using Service Principal");
@@ -118,73 +422,148 @@ async fn send_email_with_api(
             debug!("host_name: {}", host_name);
             debug!("tenant_id: {}", tenant_id);
             debug!("client_id: {}", client_id);
-            debug!("client_secret: {}", client_secret);
-            ACSClientBuilder::new()
-                .host(host_name.as_str())
-                .service_principal(
-                    tenant_id.as_str(),
-                    client_id.as_str(),
-                    client_secret.as_str(),
-                )
+            ACSClientBuilder::with_service_principal(
+                host_name.as_str(),
+                tenant_id.as_str(),
+                client_id.as_str(),
+                client_secret.as_str(),
+            )
         }
         CLIAuthenticationMethod::SharedKey => {
             info!("Using Shared Key");
             let connection_str = get_env_var("CONNECTION_STR");
-            ACSClientBuilder::new().connection_string(connection_str.as_str())
+            ACSClientBuilder::with_connection_string(connection_str.as_str())
         }
     };
 
-    let email_request = SentEmailBuilder::new()
-        .sender(sender.to_owned())
-        .content(EmailContent {
-            subject: Some("An exciting offer especially for you!".to_string()),
-            plain_text: Some("This exciting offer was created especially for you, our most loyal customer.".to_string()),
-            html: Some("<html><head><title>Exciting offer!</title></head><body><h1>This exciting offer was created especially for you, our most loyal customer.</h1></body></html>".to_string()),
-        })
-        .recipients(Recipients {
-            to: Some(vec![EmailAddress {
-                email: Some(recipient.to_owned()),
-                display_name: Some(display_name.to_owned()),
-            }]),
-            cc: None,
-            b_cc: None,
-        })
-        .user_engagement_tracking_disabled(false)
+    acs_client_builder
         .build()
-        .expect("Failed to build SentEmail");
-
-    debug!("Email request: {:#?}", email_request);
+        .expect("Failed to build ACSClient")
+}
 
-    let acs_client = acs_client_builder
-        .build()
-        .expect("Failed to build ACSClient");
+/// Builds the email to send from the CLI arguments.
+///
+/// The demo subject and bodies are used when no content flags are given, and the
+/// `REPLY_EMAIL`/`REPLY_EMAIL_DISPLAY` recipient when no recipient flags are given.
+///
+/// # Arguments
+///
+/// * `args` - The parsed `send` arguments.
+/// * `sender` - The sender's email address.
+///
+/// # Returns
+///
+/// * `Result<SentEmail, String>` - The email to send or an error message.
+fn build_email_request(args: &SendArgs, sender: &str) -> Result<SentEmail, String> {
+    if args.raw_mime.is_some() {
+        return Err("--raw-mime is only supported with the SMTP protocol".to_string());
+    }
+    let stdin = Path::new("-");
+    if args.text_file.as_deref() == Some(stdin) && args.html_file.as_deref() == Some(stdin) {
+        return Err("Only one of --text-file and --html-file can read from stdin".to_string());
+    }
+    let plain_text = match &args.text_file {
+        Some(path) => Some(read_content_file(path)?),
+        None => args.body.clone(),
+    };
+    let html = match &args.html_file {
+        Some(path) => Some(read_content_file(path)?),
+        None => args.html.clone(),
+    };
 
-    let resp_send_email = acs_client.send_email(&email_request).await;
-    match resp_send_email {
-        Ok(message_resp_id) => {
-            info!("Email was sent with message id: {}", message_resp_id);
-            loop {
-                tokio::time::sleep(time::Duration::from_secs(5)).await;
-                let resp_status = acs_client.get_email_status(&message_resp_id).await;
-                if let Ok(status) = resp_status {
-                    info!("{}\r\n", status.to_string());
-                    if matches!(
-                        status,
-                        EmailSendStatusType::Unknown
-                            | EmailSendStatusType::Canceled
-                            | EmailSendStatusType::Failed
-                            | EmailSendStatusType::Succeeded
-                    ) {
-                        break;
-                    }
-                } else {
-                    error!("Error getting email status: {:?}", resp_status);
-                    break;
-                }
-            }
+    let content = if args.subject.is_none() && plain_text.is_none() && html.is_none() {
+        EmailContent::both(
+            "An exciting offer especially for you!",
+            "This exciting offer was created especially for you, our most loyal customer.",
+            "<html><head><title>Exciting offer!</title></head><body><h1>This exciting offer was created especially for you, our most loyal customer.</h1></body></html>",
+        )
+    } else {
+        EmailContent {
+            subject: args.subject.clone(),
+            plain_text,
+            html,
         }
-        Err(e) => error!("Error sending email: {:?}", e),
+    };
+
+    let mut builder = SentEmailBuilder::new()
+        .sender(sender.to_owned())
+        .content(content);
+
+    if args.to.is_empty() && args.cc.is_empty() && args.bcc.is_empty() {
+        let recipient = get_env_var("REPLY_EMAIL");
+        let display_name = get_env_var("REPLY_EMAIL_DISPLAY");
+        builder = builder.to(EmailAddress::from(recipient).with_display_name(&display_name));
+    }
+    for address in &args.to {
+        builder = builder.to(address.clone());
+    }
+    for address in &args.cc {
+        builder = builder.cc(address.clone());
+    }
+    for address in &args.bcc {
+        builder = builder.bcc(address.clone());
+    }
+
+    if !args.attach.is_empty() {
+        let attachments = args
+            .attach
+            .iter()
+            .map(EmailAttachment::from_path)
+            .collect::<Result<Vec<_>, _>>()?;
+        builder = builder.attachments(attachments);
     }
+
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Reads an email body from a file, or from stdin when the path is `-`.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+///
+/// # Returns
+///
+/// * `Result<String, String>` - The file content, or an error if it can't be read or is empty.
+fn read_content_file(path: &Path) -> Result<String, String> {
+    let content = if path == Path::new("-") {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| format!("Failed to read stdin: {}", e))?;
+        content
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+    };
+    if content.trim().is_empty() {
+        return Err(format!("{} is empty", path.display()));
+    }
+    Ok(content)
+}
+
+/// Reads a raw MIME message from a file, or from stdin when the path is `-`.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, String>` - The message bytes, or an error if it can't be read or is empty.
+fn read_raw_mime_file(path: &Path) -> Result<Vec<u8>, String> {
+    let content = if path == Path::new("-") {
+        let mut content = Vec::new();
+        io::stdin()
+            .read_to_end(&mut content)
+            .map_err(|e| format!("Failed to read stdin: {}", e))?;
+        content
+    } else {
+        fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+    };
+    if content.is_empty() {
+        return Err(format!("{} is empty", path.display()));
+    }
+    Ok(content)
 }
 
 /// Retrieves the value of an environment variable.
@@ -200,46 +579,162 @@ fn get_env_var(var_name: &str) -> String {
     env::var(var_name).unwrap_or_else(|_| panic!("Environment variable {} is not set", var_name))
 }
 
+/// Runs the CLI.
+///
+/// Exits with a non-zero code when the send fails or the final status is not `Succeeded`.
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     pretty_env_logger::init();
     dotenv::dotenv().ok();
 
     let args = Cli::parse();
+    let command = args
+        .command
+        .unwrap_or_else(|| Command::Send(SendArgs::parse_from(["send"])));
 
-    match args.protocol {
-        CLIACSProtocol::REST => {
+    match (command, args.protocol) {
+        (Command::Send(send_args), CLIACSProtocol::REST) => {
             info!("Sending email using REST API");
             let sender = get_env_var("SENDER");
-            let recipient = get_env_var("REPLY_EMAIL");
-            let display_name = get_env_var("REPLY_EMAIL_DISPLAY");
+            let email_request = match build_email_request(&send_args, sender.as_str()) {
+                Ok(email_request) => email_request,
+                Err(e) => {
+                    error!("Failed to build email: {}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
 
-            send_email_with_api(
-                &args.auth_method,
-                sender.as_str(),
-                recipient.as_str(),
-                display_name.as_str(),
-            )
-                .await;
+            let wait_options = match send_args.poll.wait_options(!send_args.no_wait) {
+                Ok(wait_options) => wait_options,
+                Err(e) => {
+                    error!("{}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let outcome =
+                send_email_with_api(&args.auth_method, &email_request, &wait_options).await;
+            if args.json {
+                print_outcome_json(&outcome);
+            } else if let (false, Some(operation_id)) = (wait_options.wait, &outcome.operation_id) {
+                println!("{}", operation_id);
+            }
+            exit_code(outcome.is_success(wait_options.wait))
         }
-        CLIACSProtocol::SMTP => {
+        (Command::Send(send_args), CLIACSProtocol::SMTP) => {
             info!("Sending email using SMTP");
+            let raw_mime = match send_args.raw_mime.as_deref().map(read_raw_mime_file) {
+                Some(Ok(raw_mime)) => Some(raw_mime),
+                Some(Err(e)) => {
+                    error!("Failed to read raw MIME message: {}", e);
+                    return ExitCode::FAILURE;
+                }
+                None => None,
+            };
             let sender = get_env_var("SENDER");
             let recipient = get_env_var("REPLY_EMAIL");
             let smtp_server = get_env_var("SMTP_SERVER");
             let smtp_user = get_env_var("SMTP_USER");
-            let smtp_password = get_env_var("SMTP_PASSWORD");
+            let (smtp_secret, mechanisms) = match args.smtp_auth {
+                CLISmtpAuth::Basic => (
+                    get_env_var("SMTP_PASSWORD"),
+                    vec![Mechanism::Plain, Mechanism::Login],
+                ),
+                CLISmtpAuth::Xoauth2 => {
+                    match build_acs_client(&args.auth_method).access_token().await {
+                        Ok(token) => (token, vec![Mechanism::Xoauth2]),
+                        Err(e) => {
+                            error!("Failed to get SMTP access token: {}", e);
+                            return ExitCode::FAILURE;
+                        }
+                    }
+                }
+            };
 
-            send_email_with_smtp(
+            let sent = send_email_with_smtp(
                 sender.as_str(),
                 recipient.as_str(),
                 smtp_server.as_str(),
-                smtp_user.as_str(),
-                smtp_password.as_str(),
+                Credentials::new(smtp_user, smtp_secret),
+                mechanisms,
+                raw_mime.as_deref(),
             )
-                .await;
+            .await;
+            exit_code(sent)
+        }
+        (Command::Status(status_args), CLIACSProtocol::REST) => {
+            info!("Querying email status using REST API");
+            let wait_options = match status_args.poll.wait_options(status_args.watch) {
+                Ok(wait_options) => wait_options,
+                Err(e) => {
+                    error!("{}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let outcome =
+                query_email_status(&args.auth_method, &status_args.operation_id, &wait_options)
+                    .await;
+            if args.json {
+                print_outcome_json(&outcome);
+            } else if let Some(status) = &outcome.status {
+                println!("{}", status);
+            }
+            exit_code(outcome.is_success(wait_options.wait))
+        }
+        (Command::Status(_), CLIACSProtocol::SMTP) => {
+            error!("The status command is only supported with the REST protocol");
+            ExitCode::FAILURE
         }
     }
+}
+
+/// Prints the outcome as a single JSON line to stdout.
+///
+/// # Arguments
+///
+/// * `outcome` - The outcome to print.
+fn print_outcome_json(outcome: &SendOutcome) {
+    println!(
+        "{}",
+        serde_json::to_string(outcome).expect("Failed to serialize outcome")
+    );
+}
+
+/// Maps the success of a send to the process exit code.
+///
+/// # Arguments
+///
+/// * `success` - Whether the email was sent successfully.
+///
+/// # Returns
+///
+/// * `ExitCode` - `SUCCESS` or `FAILURE`.
+fn exit_code(success: bool) -> ExitCode {
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wait_options_rejects_a_poll_interval_longer_than_the_timeout() {
+        let poll = PollArgs {
+            poll_interval: 10,
+            timeout: 5,
+        };
+        assert!(poll.wait_options(true).is_err());
+        // Without polling the interval isn't used
+        assert!(poll.wait_options(false).is_ok());
+
+        let poll = PollArgs {
+            poll_interval: 5,
+            timeout: 5,
+        };
+        let wait_options = poll.wait_options(true).unwrap();
+        assert_eq!(wait_options.poll_interval, Duration::from_secs(5));
+    }
 }
@@ -0,0 +1,1399 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+//! Runs the client against a local mock of the ACS email REST API.
+
+use azure_core::auth::{AccessToken, TokenCredential};
+use azure_email_service::adapters::gateways::acs_email::{ACSClient, ACSClientBuilder};
+use azure_email_service::adapters::gateways::interceptor::{masked_headers, Interceptor};
+use azure_email_service::adapters::gateways::sent_log::InMemorySentLog;
+use azure_email_service::adapters::gateways::signing::{compute_content_sha256, compute_signature};
+use azure_email_service::domain::entities::models::{
+    AcsError, AcsErrorCode, EmailAddress, EmailContent, EmailSendStatusType, Recipients, SentEmail,
+    SentEmailBuilder,
+};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use wiremock::matchers::{
+    body_json, body_partial_json, header, header_exists, method, path, query_param,
+};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const API_VERSION: &str = "2023-01-15-preview";
+
+// A shared key client sending to the mock server over plain HTTP
+fn mock_client(server: &MockServer) -> ACSClient {
+    ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .build()
+    .unwrap()
+}
+
+// Hands out tokens expiring after `lifetime`, counting how often one is requested
+#[derive(Debug)]
+struct CountingCredential {
+    lifetime: time::Duration,
+    requests: AtomicUsize,
+}
+
+impl CountingCredential {
+    fn new(lifetime: time::Duration) -> Arc<Self> {
+        Arc::new(CountingCredential {
+            lifetime,
+            requests: AtomicUsize::new(0),
+        })
+    }
+
+    fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCredential for CountingCredential {
+    async fn get_token(&self, scopes: &[&str]) -> azure_core::Result<AccessToken> {
+        assert_eq!(scopes, ["https://communication.azure.com/.default"]);
+        let count = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(AccessToken::new(
+            format!("token-{}", count),
+            OffsetDateTime::now_utc() + self.lifetime,
+        ))
+    }
+
+    async fn clear_cache(&self) -> azure_core::Result<()> {
+        Ok(())
+    }
+}
+
+// A client authenticating with the given credential, sending to the mock server over plain HTTP
+fn token_client(server: &MockServer, credential: Arc<CountingCredential>) -> ACSClient {
+    ACSClientBuilder::with_token_credential(&server.uri(), credential)
+        .build()
+        .unwrap()
+}
+
+async fn mount_accepted(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .mount(server)
+        .await;
+}
+
+fn email() -> SentEmail {
+    SentEmailBuilder::new()
+        .sender("donotreply@example.com".to_string())
+        .subject("Subject")
+        .plain_text("Body")
+        .to("jane@example.com")
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn send_email_signs_the_request_and_returns_the_operation_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(query_param("api-version", API_VERSION))
+        .and(header_exists("authorization"))
+        .and(header_exists("x-ms-content-sha256"))
+        .and(header_exists("repeatability-request-id"))
+        .and(body_json(email()))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let id = mock_client(&server).send_email(&email()).await.unwrap();
+    assert_eq!(id, "op-1");
+}
+
+#[tokio::test]
+async fn send_email_retries_after_429_with_retry_after() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-2", "status": "Running" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let id = mock_client(&server).send_email(&email()).await.unwrap();
+    assert_eq!(id, "op-2");
+}
+
+#[tokio::test]
+async fn send_email_returns_the_error_of_a_401() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": { "code": "Denied", "message": "Denied by the resource provider." }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = mock_client(&server).send_email(&email()).await;
+    match result {
+        Err(AcsError::Api {
+            http_status,
+            code,
+            response,
+        }) => {
+            assert_eq!(http_status, Some(401));
+            assert_eq!(code, Some(AcsErrorCode::Other("Denied".to_string())));
+            assert_eq!(response.error.unwrap().code.as_deref(), Some("Denied"));
+        }
+        other => panic!("expected an API error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn get_email_status_returns_the_status_of_the_operation() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .and(query_param("api-version", API_VERSION))
+        .and(header_exists("authorization"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Succeeded" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let status = mock_client(&server).get_email_status("op-1").await.unwrap();
+    assert_eq!(status, EmailSendStatusType::Succeeded);
+}
+
+#[tokio::test]
+async fn get_email_status_escapes_the_operation_id_in_the_signed_path() {
+    use base64::{engine::general_purpose, Engine as _};
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "a/b?c d", "status": "Succeeded" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    mock_client(&server)
+        .get_email_status("a/b?c d")
+        .await
+        .unwrap();
+
+    let request = &server.received_requests().await.unwrap()[0];
+    assert_eq!(request.url.path(), "/emails/operations/a%2Fb%3Fc%20d");
+    // The signature covers the escaped path that was sent
+    let string_to_sign = format!(
+        "GET\n{}?{}\n{};{};{}",
+        request.url.path(),
+        request.url.query().unwrap(),
+        request.headers["x-ms-date"].to_str().unwrap(),
+        request.headers["host"].to_str().unwrap(),
+        request.headers["x-ms-content-sha256"].to_str().unwrap(),
+    );
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+    mac.update(string_to_sign.as_bytes());
+    let signature = general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+    assert_eq!(
+        request.headers["authorization"].to_str().unwrap(),
+        format!(
+            "HMAC-SHA256 SignedHeaders=x-ms-date;host;x-ms-content-sha256&Signature={}",
+            signature
+        )
+    );
+}
+
+#[tokio::test]
+async fn a_status_with_an_embedded_error_is_returned_as_an_api_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "op-1",
+            "status": "Failed",
+            "error": { "code": "EmailDroppedAllRecipientsSuppressed", "message": "Suppressed" }
+        })))
+        .mount(&server)
+        .await;
+
+    let result = mock_client(&server).get_email_status("op-1").await;
+    match result {
+        Err(
+            error @ AcsError::Api {
+                http_status: Some(200),
+                ..
+            },
+        ) => assert!(error
+            .to_string()
+            .contains("EmailDroppedAllRecipientsSuppressed")),
+        other => panic!("expected an API error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn an_accepted_send_with_an_embedded_error_is_returned_as_an_api_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+            "id": "op-1",
+            "status": "Failed",
+            "error": { "code": "InvalidSenderDomain", "message": "Not linked" }
+        })))
+        .mount(&server)
+        .await;
+
+    let result = mock_client(&server).send_email(&email()).await;
+    assert!(
+        matches!(
+            result,
+            Err(AcsError::Api {
+                http_status: Some(202),
+                ..
+            })
+        ),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn plain_http_is_only_accepted_for_loopback_hosts() {
+    let result = ACSClientBuilder::with_connection_string(
+        "endpoint=http://xxx.communication.azure.com/;accesskey=c2VjcmV0",
+    )
+    .build();
+    assert!(result.is_err());
+    let result = ACSClientBuilder::with_connection_string(
+        "endpoint=http://localhost:8080/;accesskey=c2VjcmV0",
+    )
+    .host_suffix("localhost")
+    .build();
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn get_email_status_decodes_a_gzip_compressed_response() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(br#"{ "id": "op-1", "status": "Succeeded" }"#)
+        .unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-encoding", "gzip")
+                .set_body_raw(compressed, "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let status = mock_client(&server).get_email_status("op-1").await.unwrap();
+    assert_eq!(status, EmailSendStatusType::Succeeded);
+    let requests = server.received_requests().await.unwrap();
+    let accept_encoding = requests[0].headers.get("accept-encoding").unwrap();
+    assert!(accept_encoding.to_str().unwrap().contains("gzip"));
+}
+
+#[tokio::test]
+async fn get_email_status_includes_a_non_json_body_in_the_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw("<html>Gateway error</html>", "text/html"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = mock_client(&server).get_email_status("op-1").await;
+    match result {
+        Err(AcsError::Client(message)) => {
+            assert!(
+                message.contains("<html>Gateway error</html>"),
+                "{}",
+                message
+            )
+        }
+        other => panic!("expected a client error, got {:?}", other),
+    }
+}
+
+// The repeatability request ID of every send received by the mock server
+async fn request_ids(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            request.headers["repeatability-request-id"]
+                .to_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn send_email_reuses_the_request_id_when_retrying() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    mount_accepted(&server).await;
+
+    mock_client(&server)
+        .send_email_idempotent(&email(), "order-42")
+        .await
+        .unwrap();
+    let ids = request_ids(&server).await;
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids[0], ids[1]);
+}
+
+#[tokio::test]
+async fn send_email_idempotent_sends_the_same_request_id_for_the_same_key() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+
+    let client = mock_client(&server);
+    client
+        .send_email_idempotent(&email(), "order-42")
+        .await
+        .unwrap();
+    client
+        .send_email_idempotent(&email(), "order-42")
+        .await
+        .unwrap();
+    client
+        .send_email_idempotent(&email(), "order-43")
+        .await
+        .unwrap();
+    let ids = request_ids(&server).await;
+    assert_eq!(ids[0], ids[1]);
+    assert_ne!(ids[0], ids[2]);
+    // A version 8 UUID, as ACS expects the ID to be a UUID
+    let id = uuid::Uuid::parse_str(&ids[0]).unwrap();
+    assert_eq!(id.get_version_num(), 8);
+}
+
+#[tokio::test]
+async fn send_email_retries_a_401_once_with_a_new_access_token() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(header("authorization", "Bearer token-1"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(header("authorization", "Bearer token-2"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let credential = CountingCredential::new(time::Duration::hours(1));
+    let client = token_client(&server, credential.clone());
+    let id = client.send_email(&email()).await.unwrap();
+    assert_eq!(id, "op-1");
+    assert_eq!(credential.requests(), 2);
+}
+
+#[tokio::test]
+async fn send_email_returns_a_second_401_as_an_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let credential = CountingCredential::new(time::Duration::hours(1));
+    let client = token_client(&server, credential.clone());
+    let result = client.send_email(&email()).await;
+    assert!(result.is_err());
+    assert_eq!(credential.requests(), 2);
+}
+
+#[tokio::test]
+async fn access_token_is_requested_once_across_sends() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(header("authorization", "Bearer token-1"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let credential = CountingCredential::new(time::Duration::hours(1));
+    let client = token_client(&server, credential.clone());
+    for _ in 0..3 {
+        client.clone().send_email(&email()).await.unwrap();
+    }
+    assert_eq!(credential.requests(), 1);
+}
+
+#[tokio::test]
+async fn access_token_is_refreshed_shortly_before_it_expires() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+
+    let credential = CountingCredential::new(time::Duration::minutes(1));
+    let client = token_client(&server, credential.clone());
+    client.send_email(&email()).await.unwrap();
+    client.send_email(&email()).await.unwrap();
+    assert_eq!(credential.requests(), 2);
+}
+
+#[tokio::test]
+async fn send_rejects_an_invalid_request_id_before_any_request() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+
+    let credential = CountingCredential::new(time::Duration::hours(1));
+    let client = token_client(&server, credential.clone());
+    let result = client
+        .send_email_with_id(&email(), "id\r\nx-injected: 1")
+        .await;
+    assert!(matches!(result, Err(AcsError::Client(_))));
+    assert_eq!(credential.requests(), 0);
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn send_email_records_the_send_and_its_retries_in_the_metrics_sink() {
+    use azure_email_service::adapters::gateways::metrics::CountingMetricsSink;
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .mount(&server)
+        .await;
+
+    let metrics = Arc::new(CountingMetricsSink::default());
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .metrics(metrics.clone())
+    .build()
+    .unwrap();
+    client.send_email(&email()).await.unwrap();
+    assert_eq!(metrics.sends(), 1);
+    assert_eq!(metrics.retries(), 1);
+}
+
+#[tokio::test]
+async fn send_falls_back_to_the_operation_location_header_and_polls_its_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "status": "Running" }))
+                .insert_header(
+                    "operation-location",
+                    format!(
+                        "{}/emails/operations/op-1?api-version=2023-03-31",
+                        server.uri()
+                    ),
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    // Only the header's API version is answered, so a status URL built from the ID would fail
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .and(query_param("api-version", "2023-03-31"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Succeeded" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = mock_client(&server);
+    let id = client.send_email(&email()).await.unwrap();
+    assert_eq!(id, "op-1");
+    let status = client.get_email_status(&id).await.unwrap();
+    assert_eq!(status, EmailSendStatusType::Succeeded);
+}
+
+#[tokio::test]
+async fn poll_many_reports_the_status_or_error_of_each_operation() {
+    let server = MockServer::start().await;
+    for (id, status) in [("op-1", "Succeeded"), ("op-2", "Failed")] {
+        Mock::given(method("GET"))
+            .and(path(format!("/emails/operations/{}", id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": id, "status": status })),
+            )
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-3"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(
+            serde_json::json!({ "error": { "code": "NotFound", "message": "Unknown operation" } }),
+        ))
+        .mount(&server)
+        .await;
+
+    let ids = ["op-1", "op-2", "op-3"].map(String::from);
+    let statuses = mock_client(&server)
+        .poll_many(
+            &ids,
+            std::time::Duration::from_millis(10),
+            std::time::Duration::from_secs(5),
+            2,
+        )
+        .await;
+    assert_eq!(statuses.len(), 3);
+    assert_eq!(
+        *statuses["op-1"].as_ref().unwrap(),
+        EmailSendStatusType::Succeeded
+    );
+    assert_eq!(
+        *statuses["op-2"].as_ref().unwrap(),
+        EmailSendStatusType::Failed
+    );
+    assert!(statuses["op-3"].is_err());
+}
+
+#[tokio::test]
+async fn get_email_status_reports_a_204_as_an_empty_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = mock_client(&server).get_email_status("op-1").await;
+    assert!(matches!(
+        result,
+        Err(AcsError::EmptyResponse { status: 204 })
+    ));
+}
+
+#[tokio::test]
+async fn poll_status_keeps_polling_after_a_204() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(ResponseTemplate::new(204))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Succeeded" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let status = mock_client(&server)
+        .poll_status(
+            "op-1",
+            std::time::Duration::from_millis(10),
+            std::time::Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(status, EmailSendStatusType::Succeeded);
+}
+
+#[tokio::test]
+async fn poll_status_stops_on_an_undecodable_status() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw("not json", "application/json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = mock_client(&server)
+        .poll_status(
+            "op-1",
+            std::time::Duration::from_millis(10),
+            std::time::Duration::from_secs(5),
+        )
+        .await;
+    assert!(matches!(result, Err(AcsError::Client(_))));
+}
+
+#[tokio::test]
+async fn send_email_reports_a_refused_connection_as_a_connect_error() {
+    // Bind and drop a listener, so nothing accepts connections on its port
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint=http://{}/;accesskey=c2VjcmV0",
+        address
+    ))
+    .host_suffix("127.0.0.1")
+    .build()
+    .unwrap();
+
+    let result = client.send_email(&email()).await;
+    assert!(matches!(result, Err(AcsError::Connect(_))));
+}
+
+#[tokio::test]
+async fn send_email_returns_a_429_without_retrying_when_retries_are_disabled() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .disable_retries()
+    .build()
+    .unwrap();
+
+    let result = client.send_email(&email()).await;
+    assert_eq!(result.unwrap_err().http_status(), Some(429));
+}
+
+#[tokio::test]
+async fn send_email_fails_instead_of_waiting_past_the_retry_budget() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "60"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .max_total_retry_duration(std::time::Duration::from_secs(1))
+    .build()
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    let result = client.send_email(&email()).await;
+    assert!(matches!(result, Err(AcsError::Timeout(_))));
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn send_and_wait_until_times_out_at_the_deadline_during_a_slow_send() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" }))
+                .set_delay(std::time::Duration::from_secs(1)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let started = std::time::Instant::now();
+    let result = mock_client(&server)
+        .send_and_wait_until(
+            &email(),
+            std::time::Duration::from_millis(10),
+            started + std::time::Duration::from_millis(100),
+        )
+        .await;
+    assert!(matches!(result, Err(AcsError::Timeout(_))), "{:?}", result);
+    assert!(started.elapsed() < std::time::Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn get_email_status_polls_a_full_operation_url_on_the_client_host() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .and(query_param("api-version", "2023-03-31"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-2"))
+        .and(query_param("api-version", API_VERSION))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-2", "status": "Succeeded" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = mock_client(&server);
+    let url = format!(
+        "{}/emails/operations/op-1?api-version=2023-03-31",
+        server.uri()
+    );
+    let status = client.get_email_status(&url).await.unwrap();
+    assert_eq!(status, EmailSendStatusType::Running);
+    // A URL without an API version gets the client's
+    let url = format!("{}/emails/operations/op-2", server.uri());
+    let status = client.get_email_status(&url).await.unwrap();
+    assert_eq!(status, EmailSendStatusType::Succeeded);
+}
+
+#[tokio::test]
+async fn get_email_status_rejects_an_operation_url_on_another_host() {
+    let server = MockServer::start().await;
+    let other = MockServer::start().await;
+
+    let url = format!(
+        "{}/emails/operations/op-1?api-version={}",
+        other.uri(),
+        API_VERSION
+    );
+    let result = mock_client(&server).get_email_status(&url).await;
+    assert!(matches!(result, Err(AcsError::Client(_))));
+    assert!(other.received_requests().await.unwrap().is_empty());
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn send_email_fills_a_blank_sender_with_the_default_sender() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(body_partial_json(
+            serde_json::json!({ "senderAddress": "noreply@example.com" }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let email = SentEmailBuilder::new()
+        .subject("Subject")
+        .plain_text("Body")
+        .to("jane@example.com")
+        .build()
+        .unwrap();
+
+    let result = mock_client(&server).send_email(&email).await;
+    assert!(matches!(result, Err(AcsError::Client(_))));
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .default_sender("noreply@example.com")
+    .build()
+    .unwrap();
+    assert_eq!(client.send_email(&email).await.unwrap(), "op-1");
+}
+
+#[tokio::test]
+async fn send_email_returns_the_rate_limit_headers_of_a_final_429() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("retry-after", "7")
+                .insert_header("x-ms-ratelimit-remaining-subscription-writes", "5")
+                .insert_header("x-ms-ratelimit-remaining-resource", "2")
+                .insert_header("x-ms-ratelimit-reset", "2026-10-16T10:00:00Z"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .disable_retries()
+    .build()
+    .unwrap();
+
+    match client.send_email(&email()).await {
+        Err(AcsError::TooManyRequests { rate_limit, .. }) => {
+            assert_eq!(
+                rate_limit.retry_after,
+                Some(std::time::Duration::from_secs(7))
+            );
+            assert_eq!(rate_limit.remaining, Some(2));
+            assert_eq!(rate_limit.reset.as_deref(), Some("2026-10-16T10:00:00Z"));
+        }
+        other => panic!("expected a TooManyRequests error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn send_raw_json_signs_and_sends_the_exact_bytes() {
+    // Whitespace and key order a serializer wouldn't reproduce
+    let body =
+        r#"{ "senderAddress":"donotreply@example.com",  "content": {"subject": "Subject"} }"#;
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(header(
+            "x-ms-content-sha256",
+            compute_content_sha256(body).as_str(),
+        ))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let id = mock_client(&server).send_raw_json(body).await.unwrap();
+    assert_eq!(id, "op-1");
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests[0].body, body.as_bytes());
+}
+
+#[tokio::test]
+async fn send_raw_json_rejects_invalid_json_before_any_request() {
+    let server = MockServer::start().await;
+    let result = mock_client(&server).send_raw_json("{ not json").await;
+    assert!(matches!(result, Err(AcsError::Client(_))));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn send_email_records_the_accepted_operation_in_the_sent_log() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+    let sent_log = Arc::new(InMemorySentLog::new());
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .sent_log(sent_log.clone())
+    .build()
+    .unwrap();
+
+    let id = client
+        .send_email_with_id(&email(), "5c3c0f7c-0000-4000-8000-000000000000")
+        .await
+        .unwrap();
+    let records = sent_log.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].operation_id, id);
+    assert_eq!(
+        records[0].request_id,
+        "5c3c0f7c-0000-4000-8000-000000000000"
+    );
+    assert_eq!(records[0].recipient_count, 1);
+}
+
+#[tokio::test]
+async fn extra_query_parameters_are_sent_and_signed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(query_param("api-version", API_VERSION))
+        .and(query_param("x-debug", "1"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .and(query_param("x-debug", "1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Succeeded" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .extra_query("x-debug", "1")
+    .build()
+    .unwrap();
+    let id = client.send_email(&email()).await.unwrap();
+    client.get_email_status(&id).await.unwrap();
+
+    // The signed path and query must be the ones that were sent, extra parameter included
+    for request in server.received_requests().await.unwrap() {
+        let url = &request.url;
+        let path_and_query = format!("{}?{}", url.path(), url.query().unwrap());
+        assert!(path_and_query.ends_with("&x-debug=1"), "{}", path_and_query);
+        let string_to_sign = format!(
+            "{}\n{}\n{};{};{}",
+            request.method,
+            path_and_query,
+            request.headers["x-ms-date"].to_str().unwrap(),
+            request.headers["host"].to_str().unwrap(),
+            compute_content_sha256(std::str::from_utf8(&request.body).unwrap()),
+        );
+        let expected = format!(
+            "HMAC-SHA256 SignedHeaders=x-ms-date;host;x-ms-content-sha256&Signature={}",
+            compute_signature(&string_to_sign, "c2VjcmV0").unwrap()
+        );
+        assert_eq!(request.headers["authorization"].to_str().unwrap(), expected);
+    }
+}
+
+#[tokio::test]
+async fn get_email_operation_returns_the_error_that_get_email_status_fails_with() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "op-1",
+            "status": "Failed",
+            "error": { "code": "InvalidRecipient", "message": "Bad address" }
+        })))
+        .mount(&server)
+        .await;
+
+    let client = mock_client(&server);
+    let operation = client.get_email_operation("op-1").await.unwrap();
+    assert_eq!(operation.id.as_deref(), Some("op-1"));
+    assert_eq!(
+        operation.status.map(|status| status.to_type()),
+        Some(EmailSendStatusType::Failed)
+    );
+    assert_eq!(
+        operation.error.and_then(|error| error.code).as_deref(),
+        Some("InvalidRecipient")
+    );
+    assert!(matches!(
+        client.get_email_status("op-1").await,
+        Err(AcsError::Api { .. })
+    ));
+}
+
+#[tokio::test]
+async fn with_shared_key_sends_to_a_loopback_http_host() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+
+    let client = ACSClientBuilder::with_shared_key(&format!("{}/", server.uri()), "c2VjcmV0")
+        .host_suffix("127.0.0.1")
+        .build()
+        .unwrap();
+    assert_eq!(client.send_email(&email()).await.unwrap(), "op-1");
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests[0].url.path(), "/emails:send");
+}
+
+#[cfg(debug_assertions)]
+#[tokio::test]
+#[should_panic(expected = "At least one recipient is required")]
+async fn send_email_panics_on_an_email_without_recipients_in_debug_builds() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+
+    let email = SentEmail {
+        headers: None,
+        sender: "donotreply@example.com".to_string(),
+        content: EmailContent::text("Subject", "Body"),
+        recipients: Recipients::default(),
+        attachments: None,
+        reply_to: None,
+        user_engagement_tracking_disabled: None,
+    };
+    let _ = mock_client(&server).send_email(&email).await;
+}
+
+#[cfg(debug_assertions)]
+#[tokio::test]
+#[should_panic(expected = "Invalid address: 'not-an-address'")]
+async fn send_email_panics_on_an_invalid_address_in_debug_builds() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+
+    let mut email = email();
+    email.reply_to = Some(vec![EmailAddress::from("not-an-address")]);
+    let _ = mock_client(&server).send_email(&email).await;
+}
+
+#[cfg(not(debug_assertions))]
+#[tokio::test]
+async fn send_email_leaves_an_invalid_address_to_the_server_in_release_builds() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": { "code": "InvalidRecipient", "message": "Invalid address" }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut email = email();
+    email.recipients.to = Some(vec![EmailAddress {
+        email: Some("not-an-address".to_string()),
+        display_name: None,
+    }]);
+    let result = mock_client(&server).send_email(&email).await;
+    assert!(matches!(result, Err(AcsError::Api { .. })));
+}
+
+#[tokio::test]
+async fn send_personalized_sends_one_substituted_email_per_recipient() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+
+    let template = EmailContent::text("Hi {{name}}", "Your code is {{code}}");
+    let recipients = [
+        (
+            EmailAddress::from("jane@example.com"),
+            HashMap::from([
+                ("name".to_string(), "Jane".to_string()),
+                ("code".to_string(), "1234".to_string()),
+            ]),
+        ),
+        (
+            EmailAddress::from("john@example.com"),
+            HashMap::from([("name".to_string(), "John".to_string())]),
+        ),
+    ];
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .default_sender("donotreply@example.com")
+    .build()
+    .unwrap();
+    let results = client
+        .send_personalized(&template, &recipients, true)
+        .await
+        .unwrap();
+    assert_eq!(results[0].as_deref().unwrap(), "op-1");
+    assert!(matches!(&results[1], Err(AcsError::Client(e)) if e.contains("{{code}}")));
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["senderAddress"], "donotreply@example.com");
+    assert_eq!(body["content"]["subject"], "Hi Jane");
+    assert_eq!(body["content"]["plainText"], "Your code is 1234");
+    assert_eq!(body["recipients"]["to"][0]["address"], "jane@example.com");
+}
+
+#[tokio::test]
+async fn send_personalized_requires_a_default_sender_before_any_request() {
+    let server = MockServer::start().await;
+    mount_accepted(&server).await;
+
+    let template = EmailContent::text("Hi {{name}}", "Body");
+    let recipients = [(EmailAddress::from("jane@example.com"), HashMap::new())];
+    let result = mock_client(&server)
+        .send_personalized(&template, &recipients, false)
+        .await;
+    assert!(matches!(result, Err(AcsError::Client(_))));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn send_batch_returns_the_result_of_each_email_in_order() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(body_partial_json(
+            serde_json::json!({ "content": { "subject": "Second" } }),
+        ))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": { "code": "InvalidRecipient", "message": "Invalid address" }
+        })))
+        .mount(&server)
+        .await;
+    mount_accepted(&server).await;
+
+    let emails: Vec<SentEmail> = ["First", "Second", "Third"]
+        .into_iter()
+        .map(|subject| {
+            SentEmailBuilder::new()
+                .sender("donotreply@example.com".to_string())
+                .subject(subject)
+                .plain_text("Body")
+                .to("jane@example.com")
+                .build()
+                .unwrap()
+        })
+        .collect();
+    let results = mock_client(&server).send_batch(&emails, 2).await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_deref().unwrap(), "op-1");
+    assert!(matches!(results[1], Err(AcsError::Api { .. })));
+    assert_eq!(results[2].as_deref().unwrap(), "op-1");
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn send_email_parses_a_known_error_code() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": { "code": "DomainNotLinked", "message": "The domain is not linked." }
+        })))
+        .mount(&server)
+        .await;
+
+    let result = mock_client(&server).send_email(&email()).await;
+    assert!(
+        matches!(
+            result,
+            Err(AcsError::Api {
+                code: Some(AcsErrorCode::DomainNotLinked),
+                ..
+            })
+        ),
+        "{:?}",
+        result
+    );
+}
+
+// Records each request with its masked authorization header, and each response status
+#[derive(Default)]
+struct RecordingInterceptor {
+    calls: std::sync::Mutex<Vec<String>>,
+}
+
+impl Interceptor for RecordingInterceptor {
+    fn before_send(&self, request: &reqwest::Request) {
+        let headers = masked_headers(request.headers());
+        self.calls.lock().unwrap().push(format!(
+            "{} {} {:?}",
+            request.method(),
+            request.url().path(),
+            headers["authorization"]
+        ));
+    }
+
+    fn after_receive(&self, response: &reqwest::Response) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(response.status().as_u16().to_string());
+    }
+}
+
+#[tokio::test]
+async fn interceptor_observes_sends_retries_and_status_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    mount_accepted(&server).await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Succeeded" })),
+        )
+        .mount(&server)
+        .await;
+
+    let interceptor = Arc::new(RecordingInterceptor::default());
+    let client = ACSClientBuilder::with_connection_string(&format!(
+        "endpoint={}/;accesskey=c2VjcmV0",
+        server.uri()
+    ))
+    .host_suffix("127.0.0.1")
+    .interceptor(interceptor.clone())
+    .build()
+    .unwrap();
+    let operation_id = client.send_email(&email()).await.unwrap();
+    client.get_email_status(&operation_id).await.unwrap();
+
+    assert_eq!(
+        *interceptor.calls.lock().unwrap(),
+        [
+            "POST /emails:send \"***\"",
+            "429",
+            "POST /emails:send \"***\"",
+            "202",
+            "GET /emails/operations/op-1 \"***\"",
+            "200",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn send_and_status_can_use_different_api_versions() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .and(query_param("api-version", "2023-03-31"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Running" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .and(query_param("api-version", "2024-07-01-preview"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Succeeded" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = mock_client(&server);
+    let id = client
+        .send_email_with_version(&email(), "2023-03-31")
+        .await
+        .unwrap();
+    let status = client
+        .get_email_status_with_version(&id, "2024-07-01-preview")
+        .await
+        .unwrap();
+    assert_eq!(status, EmailSendStatusType::Succeeded);
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].url.query(), Some("api-version=2023-03-31"));
+    assert_eq!(
+        requests[1].url.query(),
+        Some("api-version=2024-07-01-preview")
+    );
+}
+
+#[tokio::test]
+async fn status_api_version_replaces_the_version_of_the_operation_location() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails:send"))
+        .respond_with(
+            ResponseTemplate::new(202)
+                .set_body_json(serde_json::json!({ "status": "Running" }))
+                .insert_header(
+                    "operation-location",
+                    format!(
+                        "{}/emails/operations/op-1?api-version=2023-03-31",
+                        server.uri()
+                    ),
+                ),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/emails/operations/op-1"))
+        .and(query_param("api-version", "2024-07-01-preview"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "op-1", "status": "Succeeded" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = mock_client(&server);
+    let id = client.send_email(&email()).await.unwrap();
+    let status = client
+        .get_email_status_with_version(&id, "2024-07-01-preview")
+        .await
+        .unwrap();
+    assert_eq!(status, EmailSendStatusType::Succeeded);
+}
@@ -0,0 +1,164 @@
+// License: MIT
+// This file is part of the Azure Communication Services Email Client Library, an open-source project.
+// This source code is licensed under the MIT license found in the LICENSE file in the root directory of this source tree.
+
+//! Runs the client against a local HTTPS server with a self-signed certificate.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use azure_email_service::adapters::gateways::acs_email::{ACSClientBuilder, Authenticated};
+use azure_email_service::domain::entities::models::{AcsError, SentEmail, SentEmailBuilder};
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::ssl::{SslAcceptor, SslMethod};
+use openssl::x509::extension::SubjectAlternativeName;
+use openssl::x509::{X509NameBuilder, X509};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+
+// A self-signed certificate for 127.0.0.1 and its private key
+fn self_signed_certificate() -> (X509, PKey<Private>) {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "127.0.0.1").unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    let san = SubjectAlternativeName::new()
+        .ip("127.0.0.1")
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.append_extension(san).unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+    (builder.build(), key)
+}
+
+// Serve `202 Accepted` for operation `op-1` over TLS on a loopback port, returning the port.
+// Connections whose handshake fails, e.g. because the client rejects the certificate, are dropped.
+fn serve_accepted(certificate: &X509, key: &PKey<Private>) -> u16 {
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_certificate(certificate).unwrap();
+    acceptor.set_private_key(key).unwrap();
+    let acceptor = Arc::new(acceptor.build());
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let Ok(mut stream) = acceptor.accept(stream) else {
+                continue;
+            };
+            read_request(&mut stream);
+            let body = r#"{"id":"op-1","status":"Running"}"#;
+            let response = format!(
+                "HTTP/1.1 202 Accepted\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+            let _ = stream.shutdown();
+        }
+    });
+    port
+}
+
+// Read an HTTP/1.1 request with a `content-length` body
+fn read_request(stream: &mut impl Read) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let Ok(read) = stream.read(&mut buffer) else {
+            return;
+        };
+        if read == 0 {
+            return;
+        }
+        request.extend_from_slice(&buffer[..read]);
+        let Some(header_end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&request[..header_end]).to_ascii_lowercase();
+        let content_length = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .map_or(0, |value| value.trim().parse::<usize>().unwrap());
+        if request.len() >= header_end + 4 + content_length {
+            return;
+        }
+    }
+}
+
+fn builder(port: u16) -> ACSClientBuilder<Authenticated> {
+    ACSClientBuilder::with_shared_key(&format!("https://127.0.0.1:{}/", port), "c2VjcmV0")
+        .host_suffix("127.0.0.1")
+}
+
+fn email() -> SentEmail {
+    SentEmailBuilder::new()
+        .sender("donotreply@example.com".to_string())
+        .subject("Subject")
+        .plain_text("Body")
+        .to("jane@example.com")
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn send_email_rejects_an_untrusted_self_signed_certificate() {
+    let (certificate, key) = self_signed_certificate();
+    let port = serve_accepted(&certificate, &key);
+
+    let client = builder(port).build().unwrap();
+    let result = client.send_email(&email()).await;
+    assert!(
+        !matches!(result, Ok(_) | Err(AcsError::Api { .. })),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn send_email_trusts_an_added_root_certificate() {
+    let (certificate, key) = self_signed_certificate();
+    let port = serve_accepted(&certificate, &key);
+
+    let client = builder(port)
+        .add_root_certificate(&certificate.to_pem().unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(client.send_email(&email()).await.unwrap(), "op-1");
+}
+
+#[tokio::test]
+async fn send_email_accepts_any_certificate_when_validation_is_disabled() {
+    let (certificate, key) = self_signed_certificate();
+    let port = serve_accepted(&certificate, &key);
+
+    let client = builder(port).danger_accept_invalid_certs().build().unwrap();
+    assert_eq!(client.send_email(&email()).await.unwrap(), "op-1");
+}
+
+#[test]
+fn build_rejects_an_invalid_root_certificate() {
+    let result = builder(443)
+        .add_root_certificate(b"not a certificate")
+        .build();
+    assert!(result.is_err());
+}